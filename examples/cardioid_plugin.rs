@@ -0,0 +1,33 @@
+// Example curve plugin implementing the C ABI from include/polar_plugin.h.
+//
+// Build it with `cargo build --release --example cardioid_plugin` and copy
+// the library from target/release/examples/ into the plugins/ directory.
+
+use std::os::raw::c_char;
+
+#[no_mangle]
+pub extern "C" fn polar_plugin_abi_version() -> u32 {
+    1
+}
+
+#[no_mangle]
+pub extern "C" fn polar_plugin_name() -> *const c_char {
+    c"Cardioid".as_ptr()
+}
+
+#[no_mangle]
+pub extern "C" fn polar_plugin_angle_limit() -> f32 {
+    360.0
+}
+
+/// # Safety
+///
+/// `x` and `y` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn polar_plugin_angle_to_point(angle: f32, x: *mut f32, y: *mut f32) {
+    let (sin, cos) = angle.to_radians().sin_cos();
+    let radius = (1.0 + cos) / 2.0;
+
+    *x = cos * radius;
+    *y = sin * radius;
+}
@@ -0,0 +1,53 @@
+/*
+ * Curve plugin interface for Polar Graphs.
+ *
+ * A plugin is a shared library (.so, .dylib or .dll) placed in the plugins
+ * directory. It must export all of the functions below with C linkage,
+ * otherwise it is rejected when the program starts. Functions marked as
+ * optional may be left out.
+ */
+
+#ifndef POLAR_PLUGIN_H
+#define POLAR_PLUGIN_H
+
+#include <stdint.h>
+
+#define POLAR_PLUGIN_ABI_VERSION 1
+
+#ifdef _WIN32
+#define POLAR_PLUGIN_EXPORT __declspec(dllexport)
+#else
+#define POLAR_PLUGIN_EXPORT
+#endif
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Must return POLAR_PLUGIN_ABI_VERSION */
+POLAR_PLUGIN_EXPORT uint32_t polar_plugin_abi_version(void);
+
+/* Display name, a static null-terminated UTF-8 string */
+POLAR_PLUGIN_EXPORT const char *polar_plugin_name(void);
+
+/* Period of the curve in degrees, must be positive */
+POLAR_PLUGIN_EXPORT float polar_plugin_angle_limit(void);
+
+/*
+ * Writes the point for the given angle (in degrees) to x and y.
+ * The unit circle is scaled to fit the window.
+ */
+POLAR_PLUGIN_EXPORT void polar_plugin_angle_to_point(float angle, float *x, float *y);
+
+/*
+ * Optional: signed radius for the given angle (in degrees). When exported it
+ * is used instead of polar_plugin_angle_to_point, so negative radii can be
+ * told apart from points in the opposite direction.
+ */
+POLAR_PLUGIN_EXPORT float polar_plugin_angle_to_radius(float angle);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
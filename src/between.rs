@@ -0,0 +1,166 @@
+use sfml::graphics::{Color, Vertex};
+use sfml::system::{Vector2f, Vector2u};
+
+use crate::curve_file::CurveDefinition;
+use crate::curve_stats;
+use crate::radial_axis::RadialAxis;
+use crate::region::FillRule;
+use crate::svg::Svg;
+
+// Degrees between the points that outline the region
+const STEP: f32 = 0.5;
+// Intervals of Simpson's rule for the area
+const AREA_INTERVALS: usize = 3600;
+
+pub type AngleToRadius = Box<dyn Fn(f32) -> f32>;
+
+// The region inner(t) <= r <= outer(t) for angles from `from` to `to` in degrees
+pub struct BetweenCurves {
+    pub inner: AngleToRadius,
+    pub outer: AngleToRadius,
+    pub from: f32,
+    pub to: f32,
+    pub color: Color,
+    pub area: f32,
+}
+
+impl BetweenCurves {
+    pub fn new(
+        inner: AngleToRadius,
+        outer: AngleToRadius,
+        from: f32,
+        to: f32,
+        color: Color,
+    ) -> BetweenCurves {
+        let mut between = BetweenCurves {
+            inner,
+            outer,
+            from,
+            to,
+            color,
+            area: 0.0,
+        };
+        between.area = between.measure();
+        between
+    }
+
+    // 1/2 * integral of outer^2 - inner^2 over the angle in radians
+    fn measure(&self) -> f32 {
+        let integral = curve_stats::simpson(
+            |angle| {
+                let (inner, outer) = ((self.inner)(angle), (self.outer)(angle));
+                (outer * outer - inner * inner) as f64
+            },
+            self.from,
+            self.to,
+            AREA_INTERVALS,
+        );
+        (integral * 1f64.to_radians() / 2.0) as f32
+    }
+
+    // Screen points along the inner and the outer curve, placed like the curve
+    pub fn boundary(&self, axis: &RadialAxis, size: Vector2u) -> (Vec<Vector2f>, Vec<Vector2f>) {
+        let count = ((self.to - self.from).abs() / STEP).ceil().max(1.0) as usize;
+        let to_screen = |angle: f32, r: f32| {
+            let mut point = Vector2f::default();
+            CurveDefinition::radius_to_point(&mut point, angle, r);
+            axis.to_screen(point, r, angle, size)
+        };
+
+        (0..=count)
+            .map(|i| {
+                let angle = self.from + (self.to - self.from) * i as f32 / count as f32;
+                (
+                    to_screen(angle, (self.inner)(angle)),
+                    to_screen(angle, (self.outer)(angle)),
+                )
+            })
+            .unzip()
+    }
+
+    // Triangles between the two curves and the outline as pairs of line ends
+    pub fn vertecies(&self, axis: &RadialAxis, size: Vector2u) -> (Vec<Vertex>, Vec<Vertex>) {
+        let (inner, outer) = self.boundary(axis, size);
+        let line_color = Color {
+            a: 255,
+            ..self.color
+        };
+        let defined = |point: &Vector2f| point.x.is_finite() && point.y.is_finite();
+
+        let mut triangles = Vec::new();
+        for i in 1..inner.len() {
+            let quad = [inner[i - 1], outer[i - 1], outer[i], inner[i]];
+            if quad.iter().all(defined) {
+                for corner in [0, 1, 2, 0, 2, 3] {
+                    triangles.push(Vertex::with_pos_color(quad[corner], self.color));
+                }
+            }
+        }
+
+        // Both curves, closed by the radial edges at either end of the range
+        let mut lines = Vec::new();
+        let ends = [
+            (inner[0], outer[0]),
+            (inner[inner.len() - 1], outer[outer.len() - 1]),
+        ];
+        let pairs = inner
+            .windows(2)
+            .chain(outer.windows(2))
+            .map(|pair| (pair[0], pair[1]));
+        for (from, to) in pairs.chain(ends) {
+            if defined(&from) && defined(&to) {
+                lines.push(Vertex::with_pos_color(from, line_color));
+                lines.push(Vertex::with_pos_color(to, line_color));
+            }
+        }
+        (triangles, lines)
+    }
+
+    pub fn draw_to_svg(&self, svg: &mut Svg, axis: &RadialAxis, size: Vector2u) {
+        let (inner, outer) = self.boundary(axis, size);
+        let outline: Vec<Vector2f> = outer.into_iter().chain(inner.into_iter().rev()).collect();
+        svg.path(&outline, self.color, FillRule::NonZero);
+
+        let mut closed: Vec<Vertex> = outline
+            .iter()
+            .map(|point| Vertex::with_pos_color(*point, self.color))
+            .collect();
+        closed.extend(closed.first().copied());
+        svg.polyline(
+            &closed,
+            Color {
+                a: 255,
+                ..self.color
+            },
+            1.0,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::PI;
+
+    use super::*;
+
+    #[test]
+    fn area_between_circle_and_cardioid() {
+        // 1/2 * integral of (1 + cos t)^2 - 1 over [0, pi/2] is 1 + pi/8
+        let between = BetweenCurves::new(
+            Box::new(|_| 1.0),
+            Box::new(|angle| 1.0 + angle.to_radians().cos()),
+            0.0,
+            90.0,
+            Color::WHITE,
+        );
+        assert!(
+            (between.area - (1.0 + PI / 8.0)).abs() < 1e-4,
+            "{}",
+            between.area
+        );
+
+        let (triangles, lines) = between.vertecies(&RadialAxis::default(), (200, 200).into());
+        assert_eq!(triangles.len(), 180 * 6);
+        assert_eq!(lines.len(), (180 * 2 + 2) * 2);
+    }
+}
@@ -0,0 +1,102 @@
+use std::fs;
+use std::io;
+
+pub struct Config {
+    pub path: String,
+    lines: Vec<String>,
+}
+
+impl Config {
+    pub fn new(path: &str) -> Config {
+        Config {
+            path: path.to_owned(),
+            lines: Vec::new(),
+        }
+    }
+
+    pub fn load(path: &str) -> Config {
+        match fs::read_to_string(path) {
+            Ok(text) => Self::from_text(path, &text),
+            Err(error) => {
+                if error.kind() != io::ErrorKind::NotFound {
+                    eprintln!("{}: {}", path, error);
+                }
+                Self::new(path)
+            }
+        }
+    }
+
+    pub fn from_text(path: &str, text: &str) -> Config {
+        Config {
+            path: path.to_owned(),
+            lines: text.lines().map(str::to_owned).collect(),
+        }
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let mut text = self.lines.join("\n");
+        text.push('\n');
+        fs::write(&self.path, text)
+    }
+
+    pub fn section(&self, name: &str) -> Vec<(&str, &str)> {
+        self.entries()
+            .filter(|(_, section, _, _)| *section == name)
+            .map(|(_, _, key, value)| (key, value))
+            .collect()
+    }
+
+    pub fn get(&self, section: &str, key: &str) -> Option<&str> {
+        self.entries()
+            .find(|(_, entry_section, entry_key, _)| *entry_section == section && *entry_key == key)
+            .map(|(_, _, _, value)| value)
+    }
+
+    pub fn set(&mut self, section: &str, key: &str, value: &str) {
+        let line = format!("{} = {}", key, value);
+
+        let existing = self
+            .entries()
+            .find(|(_, entry_section, entry_key, _)| *entry_section == section && *entry_key == key)
+            .map(|(index, _, _, _)| index);
+        if let Some(index) = existing {
+            self.lines[index] = line;
+            return;
+        }
+
+        let header = format!("[{}]", section);
+        match self.lines.iter().position(|line| line.trim() == header) {
+            Some(index) => self.lines.insert(index + 1, line),
+            None => {
+                if self
+                    .lines
+                    .last()
+                    .is_some_and(|line| !line.trim().is_empty())
+                {
+                    self.lines.push(String::new());
+                }
+                self.lines.push(header);
+                self.lines.push(line);
+            }
+        }
+    }
+
+    fn entries(&self) -> impl Iterator<Item = (usize, &str, &str, &str)> {
+        let mut section = "";
+        self.lines
+            .iter()
+            .enumerate()
+            .filter_map(move |(index, line)| {
+                let line = line.trim();
+                if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                    None
+                } else if line.starts_with('[') && line.ends_with(']') {
+                    section = line[1..line.len() - 1].trim();
+                    None
+                } else {
+                    let (key, value) = line.split_once('=')?;
+                    Some((index, section, key.trim(), value.trim()))
+                }
+            })
+    }
+}
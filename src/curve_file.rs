@@ -0,0 +1,282 @@
+use std::cell::{Cell, RefCell};
+use std::fs;
+use std::rc::Rc;
+use std::time::{Duration, SystemTime};
+
+use sfml::graphics::Color;
+use sfml::system::{Clock, Vector2f};
+use sfml::SfBox;
+
+use crate::between::{AngleToRadius, BetweenCurves};
+use crate::config::Config;
+use crate::expression::Expression;
+use crate::intersections::Intersections;
+use crate::polar_data::{DataOptions, PolarData};
+use crate::script;
+use crate::viewer::AngleToPoint;
+
+const KEYS: [&str; 4] = ["r", "angle_limit", "color", "background"];
+const BETWEEN_KEYS: [&str; 5] = ["inner", "outer", "from", "to", "color"];
+const INTERSECT_KEYS: [&str; 3] = ["r", "angle_limit", "color"];
+
+pub type ErrorSlot = Rc<RefCell<Option<String>>>;
+// Time a script spent evaluating since the viewer last reset it
+pub type TimeSlot = Rc<Cell<Duration>>;
+
+pub struct CurveDefinition {
+    pub angle_to_point: AngleToPoint,
+    pub angle_limit: f32,
+    pub color: Option<Color>,
+    pub background: Option<Color>,
+    pub errors: Option<ErrorSlot>,
+    pub script_time: Option<TimeSlot>,
+    pub between: Option<BetweenCurves>,
+    pub intersections: Option<Intersections>,
+}
+
+impl CurveDefinition {
+    pub fn parse(config: &Config) -> Result<CurveDefinition, String> {
+        if let Some((key, _)) = config
+            .section("")
+            .into_iter()
+            .find(|(key, _)| !KEYS.contains(key))
+        {
+            return Err(format!("unknown key '{}'", key));
+        }
+
+        let mut parameters: Vec<(String, f32)> = Vec::new();
+        for (name, value) in config.section("parameters") {
+            let value = Expression::parse(value, &[], &parameters)
+                .map_err(|error| format!("parameter {}: {}", name, error))?
+                .eval(&[]);
+            parameters.push((name.to_owned(), value));
+        }
+
+        let angle_to_point: AngleToPoint = if config.section("data").is_empty() {
+            let radius = config.get("", "r").ok_or("missing curve expression 'r'")?;
+            let radius = Expression::parse(radius, &["t", "theta"], &parameters)
+                .map_err(|error| format!("r: {}", error))?;
+            Box::new(move |point, angle| {
+                let rad = angle.to_radians();
+                Self::radius_to_point(point, angle, radius.eval(&[rad, rad]))
+            })
+        } else {
+            if config.get("", "r").is_some() {
+                return Err("'r' cannot be combined with a [data] section".to_owned());
+            }
+            let (options, path) = DataOptions::parse(config)?;
+            PolarData::load(&path, &options)?.curve().angle_to_point
+        };
+
+        let angle_limit = match config.get("", "angle_limit") {
+            Some(value) => Expression::parse(value, &[], &parameters)
+                .map_err(|error| format!("angle_limit: {}", error))?
+                .eval(&[]),
+            None => 360.0,
+        };
+        if !(angle_limit > 0.0 && angle_limit.is_finite()) {
+            return Err(format!("angle_limit must be positive, got {}", angle_limit));
+        }
+
+        Ok(CurveDefinition {
+            angle_to_point,
+            angle_limit,
+            color: Self::parse_color(config, "", "color")?,
+            background: Self::parse_color(config, "", "background")?,
+            errors: None,
+            script_time: None,
+            between: Self::parse_between(config, &parameters, angle_limit)?,
+            intersections: Self::parse_intersect(config, &parameters)?,
+        })
+    }
+
+    // The optional [between] section shades the region between two curves
+    fn parse_between(
+        config: &Config,
+        parameters: &[(String, f32)],
+        angle_limit: f32,
+    ) -> Result<Option<BetweenCurves>, String> {
+        if config.section("between").is_empty() {
+            return Ok(None);
+        }
+
+        let radius = |key: &str, default: &str| -> Result<AngleToRadius, String> {
+            let text = config.get("between", key).unwrap_or(default);
+            let radius = Expression::parse(text, &["t", "theta"], parameters)
+                .map_err(|error| format!("between {}: {}", key, error))?;
+            Ok(Box::new(move |angle: f32| {
+                let rad = angle.to_radians();
+                radius.eval(&[rad, rad])
+            }))
+        };
+        let angle = |key: &str, default: f32| -> Result<f32, String> {
+            match config.get("between", key) {
+                Some(value) => Expression::parse(value, &[], parameters)
+                    .map_err(|error| format!("between {}: {}", key, error))
+                    .map(|angle| angle.eval(&[])),
+                None => Ok(default),
+            }
+        };
+
+        if let Some((key, _)) = config
+            .section("between")
+            .into_iter()
+            .find(|(key, _)| !BETWEEN_KEYS.contains(key))
+        {
+            return Err(format!("between: unknown key '{}'", key));
+        }
+        if config.get("between", "outer").is_none() {
+            return Err("between: missing curve expression 'outer'".to_owned());
+        }
+        let (from, to) = (angle("from", 0.0)?, angle("to", angle_limit)?);
+        if !(from < to && to.is_finite()) {
+            return Err(format!(
+                "between: 'from' must be below 'to', got {} and {}",
+                from, to
+            ));
+        }
+
+        Ok(Some(BetweenCurves::new(
+            radius("inner", "0")?,
+            radius("outer", "")?,
+            from,
+            to,
+            Self::parse_color(config, "between", "color")?
+                .unwrap_or(Color::rgba(255, 170, 60, 110)),
+        )))
+    }
+
+    // The optional [intersect] section is a second curve to find intersections with
+    fn parse_intersect(
+        config: &Config,
+        parameters: &[(String, f32)],
+    ) -> Result<Option<Intersections>, String> {
+        if config.section("intersect").is_empty() {
+            return Ok(None);
+        }
+
+        if let Some((key, _)) = config
+            .section("intersect")
+            .into_iter()
+            .find(|(key, _)| !INTERSECT_KEYS.contains(key))
+        {
+            return Err(format!("intersect: unknown key '{}'", key));
+        }
+        let radius = config
+            .get("intersect", "r")
+            .ok_or("intersect: missing curve expression 'r'")?;
+        let radius = Expression::parse(radius, &["t", "theta"], parameters)
+            .map_err(|error| format!("intersect r: {}", error))?;
+
+        let angle_limit = match config.get("intersect", "angle_limit") {
+            Some(value) => Expression::parse(value, &[], parameters)
+                .map_err(|error| format!("intersect angle_limit: {}", error))?
+                .eval(&[]),
+            None => 360.0,
+        };
+        if !(angle_limit > 0.0 && angle_limit.is_finite()) {
+            return Err(format!(
+                "intersect: angle_limit must be positive, got {}",
+                angle_limit
+            ));
+        }
+
+        Ok(Some(Intersections::new(
+            Box::new(move |point, angle| {
+                let rad = angle.to_radians();
+                Self::radius_to_point(point, angle, radius.eval(&[rad, rad]))
+            }),
+            angle_limit,
+            Self::parse_color(config, "intersect", "color")?.unwrap_or(Color::rgb(160, 160, 160)),
+        )))
+    }
+
+    fn parse_color(config: &Config, section: &str, key: &str) -> Result<Option<Color>, String> {
+        let value = match config.get(section, key) {
+            Some(value) => value,
+            None => return Ok(None),
+        };
+
+        Self::color_from_text(value)
+            .map(Some)
+            .ok_or_else(|| format!("{}: expected 'r, g, b[, a]' with values 0-255", key))
+    }
+
+    // "r, g, b" or "r, g, b, a" with components from 0 to 255, as everywhere in config files
+    pub fn color_from_text(value: &str) -> Option<Color> {
+        let components: Vec<i64> = value
+            .split(',')
+            .map(|component| component.trim().parse().ok())
+            .collect::<Option<_>>()?;
+        Self::color_from_components(&components)
+    }
+
+    pub fn color_from_components(components: &[i64]) -> Option<Color> {
+        let components: Vec<u8> = components
+            .iter()
+            .map(|component| u8::try_from(*component).ok())
+            .collect::<Option<_>>()?;
+        match components[..] {
+            [r, g, b] => Some(Color::rgb(r, g, b)),
+            [r, g, b, a] => Some(Color::rgba(r, g, b, a)),
+            _ => None,
+        }
+    }
+
+    pub fn radius_to_point(point: &mut Vector2f, angle: f32, radius: f32) -> f32 {
+        let (rad_sin, rad_cos) = angle.to_radians().sin_cos();
+
+        point.x = rad_cos * radius;
+        point.y = rad_sin * radius;
+        radius
+    }
+}
+
+pub struct CurveWatcher {
+    pub path: String,
+    modified: Option<Result<SystemTime, String>>,
+    clock: SfBox<Clock>,
+}
+
+impl CurveWatcher {
+    pub fn new(path: &str) -> CurveWatcher {
+        CurveWatcher {
+            path: path.to_owned(),
+            modified: None,
+            clock: Clock::start(),
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.modified = None;
+    }
+
+    pub fn poll(&mut self) -> Option<Result<CurveDefinition, String>> {
+        if self.modified.is_some() && self.clock.elapsed_time().as_seconds() < 0.5 {
+            return None;
+        }
+        self.clock.restart();
+
+        let modified = fs::metadata(&self.path)
+            .and_then(|metadata| metadata.modified())
+            .map_err(|error| error.to_string());
+        if self.modified.as_ref() == Some(&modified) {
+            return None;
+        }
+        self.modified = Some(modified.clone());
+
+        Some(
+            modified
+                .and_then(|_| fs::read_to_string(&self.path).map_err(|error| error.to_string()))
+                .and_then(|text| {
+                    if self.path.ends_with(".rhai") {
+                        script::load(&text)
+                    } else if self.path.ends_with(".csv") || self.path.ends_with(".tsv") {
+                        PolarData::parse(&text, &DataOptions::default()).map(PolarData::curve)
+                    } else {
+                        CurveDefinition::parse(&Config::from_text(&self.path, &text))
+                    }
+                }),
+        )
+    }
+}
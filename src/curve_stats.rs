@@ -0,0 +1,242 @@
+use std::fmt::Write;
+
+use sfml::system::Vector2f;
+
+use crate::data_array;
+use crate::region::{self, FillRule};
+use crate::sample_export::{json_number, json_string};
+
+const SAMPLES_PER_TURN: f32 = 3600.0;
+const MAX_SAMPLES: f32 = 200_000.0;
+const AREA_ROWS: usize = 2000;
+
+// Properties of one period of the curve in its own units
+pub struct CurveStats {
+    pub arc_length: f32,
+    pub swept_area: f32,
+    pub enclosed_area: f32,
+    pub min_r: f32,
+    pub max_r: f32,
+    pub min: Vector2f,
+    pub max: Vector2f,
+    pub lobes: usize,
+    pub samples: usize,
+}
+
+impl CurveStats {
+    pub fn measure(
+        angle_to_point: &dyn Fn(&mut Vector2f, f32) -> f32,
+        angle_limit: f32,
+    ) -> CurveStats {
+        let count = (angle_limit / 360.0 * SAMPLES_PER_TURN).clamp(16.0, MAX_SAMPLES);
+        let step = angle_limit / count.ceil();
+
+        let mut radii = Vec::new();
+        let mut points = Vec::new();
+        for angle in data_array::period_angles(angle_limit, step) {
+            let mut point = Vector2f::default();
+            radii.push(angle_to_point(&mut point, angle));
+            points.push(point);
+        }
+        let defined = |point: &Vector2f| point.x.is_finite() && point.y.is_finite();
+
+        let mut arc_length = 0.0f64;
+        let mut swept_area = 0.0f64;
+        for i in 1..points.len() {
+            let (from, to) = (points[i - 1], points[i]);
+            if defined(&from) && defined(&to) {
+                arc_length += (to.x - from.x).hypot(to.y - from.y) as f64;
+            }
+            // Trapezoidal rule for 1/2 * integral of r^2 over the angle
+            let r2 = (radii[i - 1] * radii[i - 1] + radii[i] * radii[i]) as f64 / 2.0;
+            if r2.is_finite() {
+                swept_area += r2 * step.to_radians() as f64 / 2.0;
+            }
+        }
+
+        let polygon: Vec<Vector2f> = points.iter().copied().filter(defined).collect();
+        let (min, max) = polygon.iter().fold(
+            (
+                Vector2f::new(f32::INFINITY, f32::INFINITY),
+                Vector2f::new(f32::NEG_INFINITY, f32::NEG_INFINITY),
+            ),
+            |(min, max), point| {
+                (
+                    Vector2f::new(min.x.min(point.x), min.y.min(point.y)),
+                    Vector2f::new(max.x.max(point.x), max.y.max(point.y)),
+                )
+            },
+        );
+        let finite_radii = radii.iter().copied().filter(|r| r.is_finite());
+
+        CurveStats {
+            arc_length: arc_length as f32,
+            swept_area: swept_area as f32,
+            enclosed_area: region::area(&polygon, FillRule::NonZero, AREA_ROWS),
+            min_r: finite_radii.clone().fold(f32::NAN, f32::min),
+            max_r: finite_radii.fold(f32::NAN, f32::max),
+            min,
+            max,
+            lobes: count_lobes(&radii),
+            samples: points.len(),
+        }
+    }
+
+    pub fn to_json(&self, curve_name: &str, angle_limit: f32) -> String {
+        let mut text = String::new();
+        writeln!(text, "{{").unwrap();
+        writeln!(text, "  \"curve\": {},", json_string(curve_name)).unwrap();
+        writeln!(text, "  \"angle_limit\": {},", json_number(angle_limit, 6)).unwrap();
+        writeln!(text, "  \"samples\": {},", self.samples).unwrap();
+        for (name, value) in [
+            ("arc_length", self.arc_length),
+            ("enclosed_area", self.enclosed_area),
+            ("swept_area", self.swept_area),
+            ("min_r", self.min_r),
+            ("max_r", self.max_r),
+        ] {
+            writeln!(text, "  \"{}\": {},", name, json_number(value, 6)).unwrap();
+        }
+        writeln!(
+            text,
+            "  \"bounding_box\": {{\"min_x\": {}, \"min_y\": {}, \"max_x\": {}, \"max_y\": {}}},",
+            json_number(self.min.x, 6),
+            json_number(self.min.y, 6),
+            json_number(self.max.x, 6),
+            json_number(self.max.y, 6)
+        )
+        .unwrap();
+        writeln!(text, "  \"lobes\": {}", self.lobes).unwrap();
+        writeln!(text, "}}").unwrap();
+        text
+    }
+}
+
+// Simpson's rule for the integral of f from `from` to `to`, `intervals` must be even
+pub fn simpson(mut f: impl FnMut(f32) -> f64, from: f32, to: f32, intervals: usize) -> f64 {
+    let h = (to - from) / intervals as f32;
+    let sum: f64 = (0..=intervals)
+        .map(|i| {
+            let weight = if i == 0 || i == intervals {
+                1.0
+            } else if i % 2 == 1 {
+                4.0
+            } else {
+                2.0
+            };
+            weight * f(from + h * i as f32)
+        })
+        .sum();
+    sum * h as f64 / 3.0
+}
+
+// Every petal or loop has one peak of |r|, count the peaks around the period
+// ignoring wiggles smaller than a thousandth of the largest radius
+fn count_lobes(radii: &[f32]) -> usize {
+    let values: Vec<f32> = radii
+        .iter()
+        .map(|r| if r.is_finite() { r.abs() } else { 0.0 })
+        .collect();
+    let max = values.iter().copied().fold(0.0, f32::max);
+    let tolerance = max * 1e-3;
+    if values.is_empty() || max - values.iter().copied().fold(max, f32::min) <= tolerance {
+        return 0;
+    }
+
+    // Start at the lowest point so no peak is split by the end of the period
+    let start = values
+        .iter()
+        .enumerate()
+        .min_by(|a, b| a.1.total_cmp(b.1))
+        .map_or(0, |(i, _)| i);
+
+    let mut lobes = 0;
+    let mut rising = true;
+    let mut extreme = values[start];
+    for i in 1..=values.len() {
+        let value = values[(start + i) % values.len()];
+        if rising {
+            if value > extreme {
+                extreme = value;
+            } else if value < extreme - tolerance {
+                lobes += 1;
+                rising = false;
+                extreme = value;
+            }
+        } else if value < extreme {
+            extreme = value;
+        } else if value > extreme + tolerance {
+            rising = true;
+            extreme = value;
+        }
+    }
+    lobes
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::PI;
+
+    use super::*;
+    use crate::curve_file::CurveDefinition;
+
+    fn assert_close(actual: f32, expected: f32, tolerance: f32) {
+        assert!(
+            (actual - expected).abs() <= tolerance,
+            "{} is not {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn simpson_cubic() {
+        // Exact for polynomials up to the third degree
+        let integral = simpson(|x| (x * x * x) as f64 - x as f64, 0.0, 2.0, 4);
+        assert_close(integral as f32, 2.0, 1e-6);
+    }
+
+    #[test]
+    fn circle_and_rose() {
+        let circle = CurveStats::measure(
+            &|point, angle| CurveDefinition::radius_to_point(point, angle, 1.0),
+            360.0,
+        );
+        assert_close(circle.arc_length, 2.0 * PI, 1e-3);
+        assert_close(circle.swept_area, PI, 1e-3);
+        assert_close(circle.enclosed_area, PI, 1e-2);
+        assert_close(circle.min.x, -1.0, 1e-4);
+        assert_close(circle.max.y, 1.0, 1e-4);
+        assert_eq!(circle.lobes, 0);
+
+        let rose = CurveStats::measure(
+            &|point, angle| {
+                let r = (2.0 * angle.to_radians()).cos();
+                CurveDefinition::radius_to_point(point, angle, r)
+            },
+            360.0,
+        );
+        assert_close(rose.swept_area, PI / 2.0, 1e-3);
+        assert_close(rose.enclosed_area, PI / 2.0, 1e-2);
+        assert_close(rose.min_r, -1.0, 1e-4);
+        assert_eq!(rose.lobes, 4);
+    }
+
+    #[test]
+    fn inner_loop_counts_once() {
+        let limacon = CurveStats::measure(
+            &|point, angle| {
+                let r = 1.0 + 2.0 * angle.to_radians().cos();
+                CurveDefinition::radius_to_point(point, angle, r)
+            },
+            360.0,
+        );
+
+        // The inner loop is swept twice but enclosed once
+        let inner_loop = PI - 3.0 * 3f32.sqrt() / 2.0;
+        assert_close(limacon.swept_area, 3.0 * PI, 1e-3);
+        assert_close(limacon.enclosed_area, 3.0 * PI - inner_loop, 2e-2);
+        assert_eq!(limacon.lobes, 2);
+        assert!(limacon.to_json("limacon", 360.0).contains("\"lobes\": 2\n"));
+    }
+}
@@ -0,0 +1,373 @@
+use std::iter;
+use std::ops::Range;
+
+use sfml::graphics::{Color, Vertex};
+use sfml::system::{Vector2f, Vector2u};
+
+pub fn update(
+    vertecies: &mut [Vertex],
+    angle_to_vertex: &dyn Fn(&mut Vertex, f32),
+    angle: f32,
+    size: Vector2u,
+    margin: f32,
+    cutoff: bool,
+) {
+    let len_last = match vertecies.len().checked_sub(1) {
+        Some(len_last) => len_last,
+        None => return,
+    };
+
+    vertecies.rotate_left(1);
+
+    if cutoff {
+        // A single vertex is the newest one and stays opaque
+        vertecies.iter_mut().enumerate().for_each(|(i, vertex)| {
+            vertex.color.a = (i * 0xFF)
+                .checked_div(len_last)
+                .map_or(0xFF, |alpha| alpha as u8)
+        });
+    }
+
+    angle_to_vertex(&mut vertecies[len_last], angle);
+    unit_to_screen_point(&mut vertecies[len_last].position, size, margin);
+}
+
+pub fn resize(
+    vertecies: &mut Vec<Vertex>,
+    desired_count: usize,
+    color: Color,
+    angle_to_vertex: &dyn Fn(&mut Vertex, f32),
+    size: Vector2u,
+    margin: f32,
+) {
+    let old_len = vertecies.len();
+    if desired_count == old_len {
+        return;
+    }
+
+    if desired_count < old_len {
+        vertecies.drain(0..old_len - desired_count);
+        vertecies.shrink_to_fit();
+    } else {
+        vertecies.reserve(desired_count - old_len);
+
+        if old_len == 0 {
+            let mut vertex = Vertex::new((0.0, 0.0).into(), color, (0.0, 0.0).into());
+            angle_to_vertex(&mut vertex, 0.0);
+            unit_to_screen_point(&mut vertex.position, size, margin);
+            vertecies.push(vertex);
+        }
+
+        let last_elem = *vertecies.last().unwrap();
+        vertecies.resize(desired_count, last_elem);
+    }
+}
+
+pub fn reset(
+    vertecies: &mut [Vertex],
+    angle_to_vertex: &dyn Fn(&mut Vertex, f32),
+    angle: f32,
+    size: Vector2u,
+    margin: f32,
+) {
+    for vertex in vertecies.iter_mut() {
+        angle_to_vertex(vertex, angle);
+        unit_to_screen_point(&mut vertex.position, size, margin);
+    }
+}
+
+// Angles of one full cycle: the reset angle 0 and every step up to and
+// including the first one that reaches the angle limit, none for a step
+// that would never get there
+pub fn period_angles(angle_limit: f32, angle_delta: f32) -> impl Iterator<Item = f32> {
+    let start = (angle_delta > 0.0).then_some(0.0);
+    iter::successors(start, move |angle| {
+        (*angle < angle_limit).then_some(angle + angle_delta)
+    })
+}
+
+pub fn rescale(vertecies: &mut [Vertex], old_size: Vector2u, new_size: Vector2u, margin: f32) {
+    let old_radius = get_radius(old_size, margin);
+    let new_radius = get_radius(new_size, margin);
+
+    for vertex in vertecies.iter_mut() {
+        vertex.position.x = (vertex.position.x - old_size.x as f32 / 2.0) / old_radius * new_radius
+            + new_size.x as f32 / 2.0;
+        vertex.position.y = (vertex.position.y - old_size.y as f32 / 2.0) / old_radius * new_radius
+            + new_size.y as f32 / 2.0;
+    }
+}
+
+// Scales every vertex towards (or away from) the center of the screen
+pub fn scale(vertecies: &mut [Vertex], size: Vector2u, factor: f32) {
+    let center = Vector2f::new(size.x as f32 / 2.0, size.y as f32 / 2.0);
+    for vertex in vertecies.iter_mut() {
+        vertex.position = center + (vertex.position - center) * factor;
+    }
+}
+
+// Ranges of consecutive points that are drawn as one line: the strip breaks
+// at points that are undefined or further than `clip` pixels from the center
+// on either axis, and between neighbours more than `max_jump` pixels apart
+pub fn segments(
+    points: impl Iterator<Item = Vector2f>,
+    center: Vector2f,
+    clip: f32,
+    max_jump: f32,
+) -> Vec<Range<usize>> {
+    let mut segments = Vec::new();
+    let mut start = None;
+    let mut previous = center;
+    let mut count = 0;
+
+    for (i, point) in points.enumerate() {
+        let offset = point - center;
+        let visible = offset.x.abs() <= clip && offset.y.abs() <= clip;
+        let jump = point - previous;
+        match start {
+            Some(first) if !visible || (max_jump > 0.0 && jump.x.hypot(jump.y) > max_jump) => {
+                segments.push(first..i);
+                start = visible.then_some(i);
+            }
+            None if visible => start = Some(i),
+            _ => (),
+        }
+        previous = point;
+        count = i + 1;
+    }
+    if let Some(first) = start {
+        segments.push(first..count);
+    }
+
+    segments
+}
+
+pub const MARGIN: f32 = 50.0;
+
+// Keeps a margin in pixels, but never drops to zero or below for tiny sizes
+pub fn get_radius(size: Vector2u, margin: f32) -> f32 {
+    (size.x.min(size.y) as f32 / 2.0 - margin).max(1.0)
+}
+
+pub fn screen_to_unit_point(point: &mut Vector2f, size: Vector2u, margin: f32) {
+    let radius = get_radius(size, margin);
+    point.x = (point.x - size.x as f32 / 2.0) / radius;
+    point.y = -(point.y - size.y as f32 / 2.0) / radius;
+}
+
+pub fn unit_to_screen_point(point: &mut Vector2f, size: Vector2u, margin: f32) {
+    let radius = get_radius(size, margin);
+    point.x = point.x * radius + size.x as f32 / 2.0;
+    point.y = -point.y * radius + size.y as f32 / 2.0;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use proptest::prelude::*;
+
+    fn rose(vertex: &mut Vertex, angle: f32) {
+        let (sin, cos) = angle.to_radians().sin_cos();
+        let r = (angle * 0.8).to_radians().cos();
+        vertex.position.x = r * cos;
+        vertex.position.y = r * sin;
+    }
+
+    fn filled(count: usize, size: Vector2u) -> Vec<Vertex> {
+        let mut vertecies = Vec::new();
+        resize(&mut vertecies, count, Color::WHITE, &rose, size, MARGIN);
+        reset(&mut vertecies, &rose, 0.0, size, MARGIN);
+        vertecies
+    }
+
+    fn size() -> impl Strategy<Value = Vector2u> {
+        (0..5000u32, 0..5000u32).prop_map(Vector2u::from)
+    }
+
+    // Window sizes are clamped to at least 300 pixels on resize
+    fn window_size() -> impl Strategy<Value = Vector2u> {
+        (300..5000u32, 300..5000u32).prop_map(Vector2u::from)
+    }
+
+    #[derive(Clone, Debug)]
+    enum Operation {
+        Resize(usize),
+        Update(f32, bool),
+        Reset(f32),
+        Rescale(Vector2u),
+    }
+
+    fn operation() -> impl Strategy<Value = Operation> {
+        prop_oneof![
+            (0..2000usize).prop_map(Operation::Resize),
+            (-1e4..1e4f32, any::<bool>())
+                .prop_map(|(angle, cutoff)| Operation::Update(angle, cutoff)),
+            (-1e4..1e4f32).prop_map(Operation::Reset),
+            size().prop_map(Operation::Rescale),
+        ]
+    }
+
+    #[test]
+    fn period_angles_need_a_positive_step() {
+        assert_eq!(period_angles(2.0, 1.0).collect::<Vec<_>>(), [0.0, 1.0, 2.0]);
+        assert_eq!(period_angles(360.0, 0.0).count(), 0);
+        assert_eq!(period_angles(360.0, -1.0).count(), 0);
+    }
+
+    #[test]
+    fn update_empty_buffer() {
+        let mut vertecies = Vec::new();
+        update(&mut vertecies, &rose, 10.0, (800, 600).into(), MARGIN, true);
+        assert!(vertecies.is_empty());
+    }
+
+    #[test]
+    fn update_single_vertex_is_opaque() {
+        let size = (800, 600).into();
+        let mut vertecies = filled(1, size);
+        update(&mut vertecies, &rose, 90.0, size, MARGIN, true);
+
+        let mut expected = Vertex::default();
+        rose(&mut expected, 90.0);
+        unit_to_screen_point(&mut expected.position, size, MARGIN);
+        assert_eq!(vertecies[0].color.a, 0xFF);
+        assert_eq!(vertecies[0].position, expected.position);
+    }
+
+    #[test]
+    fn resize_from_empty_starts_at_angle_zero() {
+        let size = (800, 600).into();
+        let vertecies = filled(3, size);
+        assert!(vertecies
+            .iter()
+            .all(|vertex| vertex.position == Vector2f::new(400.0 + 250.0, 300.0)));
+    }
+
+    #[test]
+    fn segments_break_at_gaps_and_jumps() {
+        let points = [
+            (0.0, 0.0),
+            (1.0, 0.0),
+            (f32::NAN, 0.0),
+            (2.0, 0.0),
+            (2.0, 1.0),
+            (9.0, 1.0),
+            (9.0, 2.0),
+            (99.0, 0.0),
+        ]
+        .map(|(x, y)| Vector2f::new(x, y));
+        let center = Vector2f::default();
+
+        assert_eq!(
+            segments(points.into_iter(), center, 10.0, 5.0),
+            [0..2, 3..5, 5..7]
+        );
+        assert_eq!(
+            segments(points.into_iter(), center, 10.0, 0.0),
+            [0..2, 3..7]
+        );
+        assert_eq!(segments(iter::empty(), center, 10.0, 5.0), []);
+    }
+
+    proptest! {
+        #[test]
+        fn length_equals_desired_count(
+            counts in prop::collection::vec(0..3000usize, 1..8),
+            size in size(),
+        ) {
+            let mut vertecies = Vec::new();
+            for (i, count) in counts.into_iter().enumerate() {
+                resize(&mut vertecies, count, Color::WHITE, &rose, size, MARGIN);
+                prop_assert_eq!(vertecies.len(), count);
+
+                update(&mut vertecies, &rose, i as f32, size, MARGIN, true);
+                prop_assert_eq!(vertecies.len(), count);
+            }
+        }
+
+        #[test]
+        fn alpha_is_monotonic(
+            count in 1..1000usize,
+            steps in 0..2000usize,
+            angle_delta in 0.01..10.0f32,
+        ) {
+            let size = (800, 600).into();
+            let mut vertecies = filled(count, size);
+            for step in 0..=steps {
+                update(&mut vertecies, &rose, step as f32 * angle_delta, size, MARGIN, true);
+            }
+
+            prop_assert!(vertecies
+                .windows(2)
+                .all(|pair| pair[0].color.a <= pair[1].color.a));
+            prop_assert_eq!(vertecies[0].color.a, if count == 1 { 0xFF } else { 0 });
+            prop_assert_eq!(vertecies[count - 1].color.a, 0xFF);
+        }
+
+        #[test]
+        fn rescale_round_trips(
+            from in window_size(),
+            to in window_size(),
+            angles in prop::collection::vec(0.0..720.0f32, 1..64),
+        ) {
+            let mut vertecies: Vec<Vertex> = angles
+                .iter()
+                .map(|angle| {
+                    let mut vertex = Vertex::default();
+                    rose(&mut vertex, *angle);
+                    unit_to_screen_point(&mut vertex.position, from, MARGIN);
+                    vertex
+                })
+                .collect();
+            let original = vertecies.clone();
+
+            rescale(&mut vertecies, from, to, MARGIN);
+            rescale(&mut vertecies, to, from, MARGIN);
+
+            let tolerance = 1e-5 * from.x.max(from.y).max(to.x).max(to.y) as f32;
+            for (vertex, original) in vertecies.iter().zip(&original) {
+                prop_assert!((vertex.position.x - original.position.x).abs() <= tolerance);
+                prop_assert!((vertex.position.y - original.position.y).abs() <= tolerance);
+            }
+        }
+
+        #[test]
+        fn screen_point_round_trips(
+            size in size(),
+            margin in 0.0..200.0f32,
+            x in -2.0..2.0f32,
+            y in -2.0..2.0f32,
+        ) {
+            let mut point = Vector2f::new(x, y);
+            unit_to_screen_point(&mut point, size, margin);
+            screen_to_unit_point(&mut point, size, margin);
+            prop_assert!((point.x - x).abs() < 1e-3);
+            prop_assert!((point.y - y).abs() < 1e-3);
+        }
+
+        #[test]
+        fn operations_never_panic(
+            size in size(),
+            operations in prop::collection::vec(operation(), 0..32),
+        ) {
+            let mut vertecies = Vec::new();
+            let mut size = size;
+            for operation in operations {
+                match operation {
+                    Operation::Resize(count) => {
+                        resize(&mut vertecies, count, Color::WHITE, &rose, size, MARGIN)
+                    }
+                    Operation::Update(angle, cutoff) => {
+                        update(&mut vertecies, &rose, angle, size, MARGIN, cutoff)
+                    }
+                    Operation::Reset(angle) => reset(&mut vertecies, &rose, angle, size, MARGIN),
+                    Operation::Rescale(new_size) => {
+                        rescale(&mut vertecies, size, new_size, MARGIN);
+                        size = new_size;
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,390 @@
+use std::f32::consts;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Function {
+    Sin,
+    Cos,
+    Tan,
+    Asin,
+    Acos,
+    Atan,
+    Atan2,
+    Sinh,
+    Cosh,
+    Tanh,
+    Sqrt,
+    Cbrt,
+    Abs,
+    Exp,
+    Ln,
+    Log10,
+    Log2,
+    Floor,
+    Ceil,
+    Round,
+    Sign,
+    Min,
+    Max,
+    Pow,
+    Mod,
+}
+
+impl Function {
+    fn from_name(name: &str) -> Option<Function> {
+        Some(match name {
+            "sin" => Function::Sin,
+            "cos" => Function::Cos,
+            "tan" => Function::Tan,
+            "asin" => Function::Asin,
+            "acos" => Function::Acos,
+            "atan" => Function::Atan,
+            "atan2" => Function::Atan2,
+            "sinh" => Function::Sinh,
+            "cosh" => Function::Cosh,
+            "tanh" => Function::Tanh,
+            "sqrt" => Function::Sqrt,
+            "cbrt" => Function::Cbrt,
+            "abs" => Function::Abs,
+            "exp" => Function::Exp,
+            "ln" => Function::Ln,
+            "log10" => Function::Log10,
+            "log2" => Function::Log2,
+            "floor" => Function::Floor,
+            "ceil" => Function::Ceil,
+            "round" => Function::Round,
+            "sign" => Function::Sign,
+            "min" => Function::Min,
+            "max" => Function::Max,
+            "pow" => Function::Pow,
+            "mod" => Function::Mod,
+            _ => return None,
+        })
+    }
+
+    fn arity(self) -> usize {
+        match self {
+            Function::Atan2 | Function::Min | Function::Max | Function::Pow | Function::Mod => 2,
+            _ => 1,
+        }
+    }
+
+    fn apply(self, args: &[f32]) -> f32 {
+        match self {
+            Function::Sin => args[0].sin(),
+            Function::Cos => args[0].cos(),
+            Function::Tan => args[0].tan(),
+            Function::Asin => args[0].asin(),
+            Function::Acos => args[0].acos(),
+            Function::Atan => args[0].atan(),
+            Function::Atan2 => args[0].atan2(args[1]),
+            Function::Sinh => args[0].sinh(),
+            Function::Cosh => args[0].cosh(),
+            Function::Tanh => args[0].tanh(),
+            Function::Sqrt => args[0].sqrt(),
+            Function::Cbrt => args[0].cbrt(),
+            Function::Abs => args[0].abs(),
+            Function::Exp => args[0].exp(),
+            Function::Ln => args[0].ln(),
+            Function::Log10 => args[0].log10(),
+            Function::Log2 => args[0].log2(),
+            Function::Floor => args[0].floor(),
+            Function::Ceil => args[0].ceil(),
+            Function::Round => args[0].round(),
+            Function::Sign => {
+                if args[0] == 0.0 {
+                    0.0
+                } else {
+                    args[0].signum()
+                }
+            }
+            Function::Min => args[0].min(args[1]),
+            Function::Max => args[0].max(args[1]),
+            Function::Pow => args[0].powf(args[1]),
+            Function::Mod => args[0].rem_euclid(args[1]),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum Expression {
+    Number(f32),
+    Variable(usize),
+    Negate(Box<Expression>),
+    Binary(char, Box<Expression>, Box<Expression>),
+    Call(Function, Vec<Expression>),
+}
+
+impl Expression {
+    pub fn parse(
+        text: &str,
+        variables: &[&str],
+        constants: &[(String, f32)],
+    ) -> Result<Expression, String> {
+        let mut parser = Parser {
+            text,
+            position: 0,
+            variables,
+            constants,
+        };
+        let expression = parser.expression()?;
+        parser.skip_whitespace();
+        if parser.position < text.len() {
+            return Err(parser.error("unexpected input"));
+        }
+        Ok(expression)
+    }
+
+    pub fn eval(&self, variables: &[f32]) -> f32 {
+        match self {
+            Expression::Number(value) => *value,
+            Expression::Variable(index) => variables[*index],
+            Expression::Negate(operand) => -operand.eval(variables),
+            Expression::Binary(operator, left, right) => {
+                let (left, right) = (left.eval(variables), right.eval(variables));
+                match operator {
+                    '+' => left + right,
+                    '-' => left - right,
+                    '*' => left * right,
+                    '/' => left / right,
+                    '%' => left % right,
+                    _ => left.powf(right),
+                }
+            }
+            Expression::Call(function, args) => {
+                let mut values = [0.0; 2];
+                for (value, arg) in values.iter_mut().zip(args) {
+                    *value = arg.eval(variables);
+                }
+                function.apply(&values)
+            }
+        }
+    }
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    position: usize,
+    variables: &'a [&'a str],
+    constants: &'a [(String, f32)],
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &str) -> String {
+        format!("{} at column {}", message, self.position + 1)
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = &self.text[self.position..];
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        self.text[self.position..].chars().next()
+    }
+
+    fn eat(&mut self, expected: char) -> bool {
+        if self.peek() == Some(expected) {
+            self.position += expected.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expression(&mut self) -> Result<Expression, String> {
+        let mut left = self.term()?;
+        while let Some(operator @ ('+' | '-')) = self.peek() {
+            self.position += 1;
+            left = Expression::Binary(operator, Box::new(left), Box::new(self.term()?));
+        }
+        Ok(left)
+    }
+
+    fn term(&mut self) -> Result<Expression, String> {
+        let mut left = self.unary()?;
+        while let Some(operator @ ('*' | '/' | '%')) = self.peek() {
+            self.position += 1;
+            left = Expression::Binary(operator, Box::new(left), Box::new(self.unary()?));
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Expression, String> {
+        if self.eat('-') {
+            Ok(Expression::Negate(Box::new(self.unary()?)))
+        } else if self.eat('+') {
+            self.unary()
+        } else {
+            self.power()
+        }
+    }
+
+    fn power(&mut self) -> Result<Expression, String> {
+        let base = self.atom()?;
+        if self.eat('^') {
+            Ok(Expression::Binary(
+                '^',
+                Box::new(base),
+                Box::new(self.unary()?),
+            ))
+        } else {
+            Ok(base)
+        }
+    }
+
+    fn atom(&mut self) -> Result<Expression, String> {
+        match self.peek() {
+            Some('(') => {
+                self.position += 1;
+                let expression = self.expression()?;
+                if !self.eat(')') {
+                    return Err(self.error("expected ')'"));
+                }
+                Ok(expression)
+            }
+            Some(c) if c.is_ascii_digit() || c == '.' => self.number(),
+            Some(c) if c.is_ascii_alphabetic() || c == '_' => self.identifier(),
+            Some(_) => Err(self.error("unexpected character")),
+            None => Err(self.error("unexpected end of expression")),
+        }
+    }
+
+    fn number(&mut self) -> Result<Expression, String> {
+        let start = self.position;
+        let rest = &self.text[start..];
+        let mut length = rest
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(rest.len());
+
+        let exponent = &rest[length..];
+        if exponent.starts_with(['e', 'E']) {
+            let digits = exponent[1..].trim_start_matches(['+', '-']);
+            let digit_count = digits
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(digits.len());
+            if digit_count > 0 {
+                length += exponent.len() - digits.len() + digit_count;
+            }
+        }
+
+        self.position += length;
+        rest[..length]
+            .parse()
+            .map(Expression::Number)
+            .map_err(|_| format!("invalid number at column {}", start + 1))
+    }
+
+    fn identifier(&mut self) -> Result<Expression, String> {
+        let start = self.position;
+        let rest = &self.text[start..];
+        let length = rest
+            .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+            .unwrap_or(rest.len());
+        let name = &rest[..length];
+        self.position += length;
+
+        if self.eat('(') {
+            let function = Function::from_name(name)
+                .ok_or_else(|| format!("unknown function '{}' at column {}", name, start + 1))?;
+
+            let mut args = Vec::new();
+            if !self.eat(')') {
+                loop {
+                    args.push(self.expression()?);
+                    if self.eat(')') {
+                        break;
+                    }
+                    if !self.eat(',') {
+                        return Err(self.error("expected ',' or ')'"));
+                    }
+                }
+            }
+
+            if args.len() != function.arity() {
+                return Err(format!(
+                    "'{}' takes {} argument(s) at column {}",
+                    name,
+                    function.arity(),
+                    start + 1
+                ));
+            }
+            return Ok(Expression::Call(function, args));
+        }
+
+        if let Some(index) = self.variables.iter().position(|variable| *variable == name) {
+            return Ok(Expression::Variable(index));
+        }
+
+        if let Some((_, value)) = self
+            .constants
+            .iter()
+            .rev()
+            .find(|(constant, _)| constant == name)
+        {
+            return Ok(Expression::Number(*value));
+        }
+
+        match name {
+            "pi" => Ok(Expression::Number(consts::PI)),
+            "tau" => Ok(Expression::Number(consts::TAU)),
+            "e" => Ok(Expression::Number(consts::E)),
+            _ => Err(format!("unknown name '{}' at column {}", name, start + 1)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(text: &str) -> f32 {
+        Expression::parse(text, &["t"], &[("k".to_owned(), 3.0)])
+            .unwrap()
+            .eval(&[2.0])
+    }
+
+    fn error(text: &str) -> String {
+        Expression::parse(text, &["t"], &[]).unwrap_err()
+    }
+
+    #[test]
+    fn precedence_and_associativity() {
+        assert_eq!(eval("1 + 2 * 3"), 7.0);
+        assert_eq!(eval("(1 + 2) * 3"), 9.0);
+        assert_eq!(eval("8 - 4 - 2"), 2.0);
+        assert_eq!(eval("8 / 4 / 2"), 1.0);
+        assert_eq!(eval("7 % 4 * 2"), 6.0);
+        // ^ binds tighter than unary minus and groups to the right
+        assert_eq!(eval("-t^2"), -4.0);
+        assert_eq!(eval("(-t)^2"), 4.0);
+        assert_eq!(eval("2^3^2"), 512.0);
+        assert_eq!(eval("2^-1"), 0.5);
+        assert_eq!(eval("2 * k^2"), 18.0);
+        assert_eq!(eval("--t + +1"), 3.0);
+    }
+
+    #[test]
+    fn numbers_names_and_calls() {
+        assert_eq!(eval("1.5e2"), 150.0);
+        assert_eq!(eval("2E-1"), 0.2);
+        assert_eq!(eval(".5e+1"), 5.0);
+        assert_eq!(eval("max(t, k) + min(t, k)"), 5.0);
+        assert_eq!(eval("mod(-1, k)"), 2.0);
+        assert_eq!(eval("sign(0) + pi - pi"), 0.0);
+    }
+
+    #[test]
+    fn errors_name_the_column() {
+        assert_eq!(error("cos(t, 1)"), "'cos' takes 1 argument(s) at column 1");
+        assert_eq!(error("1 + max(t)"), "'max' takes 2 argument(s) at column 5");
+        assert_eq!(error("foo(t)"), "unknown function 'foo' at column 1");
+        assert_eq!(error("2 * x"), "unknown name 'x' at column 5");
+        assert_eq!(error("1 2"), "unexpected input at column 3");
+        // An e without digits is not an exponent, and names do not multiply
+        assert_eq!(error("2e"), "unexpected input at column 2");
+        assert_eq!(error("(t + 1"), "expected ')' at column 7");
+        assert_eq!(error("t *"), "unexpected end of expression at column 4");
+        assert_eq!(error("1..2"), "invalid number at column 1");
+    }
+}
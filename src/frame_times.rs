@@ -0,0 +1,68 @@
+use std::collections::VecDeque;
+
+use sfml::graphics::{Color, FloatRect, Vertex};
+
+pub struct FrameTimes {
+    samples: VecDeque<f32>,
+    capacity: usize,
+}
+
+impl FrameTimes {
+    pub fn new(capacity: usize) -> FrameTimes {
+        FrameTimes {
+            samples: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    pub fn push(&mut self, seconds: f32) {
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(seconds);
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+
+    pub fn average(&self) -> f32 {
+        if self.samples.is_empty() {
+            0.0
+        } else {
+            self.samples.iter().sum::<f32>() / self.samples.len() as f32
+        }
+    }
+
+    pub fn min(&self) -> f32 {
+        self.samples.iter().copied().reduce(f32::min).unwrap_or(0.0)
+    }
+
+    pub fn max(&self) -> f32 {
+        self.samples.iter().copied().reduce(f32::max).unwrap_or(0.0)
+    }
+
+    pub fn sparkline(&self, bounds: FloatRect, color: Color) -> Vec<Vertex> {
+        let max = self.max();
+        if max <= 0.0 {
+            return Vec::new();
+        }
+
+        let step = bounds.width / (self.capacity.max(2) - 1) as f32;
+        let bottom = bounds.top + bounds.height;
+        self.samples
+            .iter()
+            .enumerate()
+            .map(|(i, sample)| {
+                Vertex::with_pos_color(
+                    (
+                        bounds.left + i as f32 * step,
+                        bottom - sample / max * bounds.height,
+                    )
+                        .into(),
+                    color,
+                )
+            })
+            .collect()
+    }
+}
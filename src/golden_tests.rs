@@ -0,0 +1,257 @@
+use std::env;
+use std::fs::{self, File};
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+use sfml::system::Vector2u;
+
+use crate::config::Config;
+use crate::curve_file::CurveDefinition;
+use crate::radial_axis::{Fit, NegativeRadius};
+use crate::raster::{self, Image};
+use crate::region::FillRule;
+use crate::rose;
+use crate::viewer::{App, Flags};
+
+const GOLDEN_DIRECTORY: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden");
+const DIFF_DIRECTORY: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/target/golden-diff");
+
+// Pixels may differ by this much per channel before they count as mismatched,
+// and up to this share of pixels may mismatch, which absorbs single pixel
+// shifts caused by platform differences in sin/cos rounding
+const CHANNEL_TOLERANCE: u8 = 16;
+const MISMATCH_TOLERANCE: f32 = 0.002;
+
+fn rose_app(n: u8, d: u8) -> App {
+    let mut app = App::new();
+    let k = n as f32 / d as f32;
+    app.plgin_angle_to_point = Box::new(move |point, angle| rose::angle_to_point(point, angle, k));
+    app.angle_limit = rose::angle_limit(n, d);
+    app
+}
+
+fn curve_app(name: &str, text: &str) -> App {
+    let config = Config::from_text(name, text);
+    let mut app = App::new();
+    app.load_curve(CurveDefinition::parse(&config).unwrap());
+    app
+}
+
+// Same steps as the F2 export: one full period without the tail fade
+fn render_full(mut app: App, size: Vector2u) -> Image {
+    app.size = size;
+    app.flags.insert(Flags::NO_CUTOFF);
+    app.desired_count = (app.angle_limit / app.angle_delta).round() as usize + 1;
+    app.resize_data_array();
+    app.reset_data_array();
+    while app.angle < app.angle_limit {
+        app.angle += app.angle_delta;
+        app.update_data_array();
+    }
+
+    raster::draw_frame(size, app.background, &app.layers(), &app.vertecies)
+}
+
+// Same steps as the animation: a tail of desired_count points with fading alpha
+fn render_tail(mut app: App, size: Vector2u, desired_count: usize, angle: f32) -> Image {
+    app.size = size;
+    app.desired_count = desired_count;
+    app.resize_data_array();
+    app.reset_data_array();
+    while app.angle < angle {
+        app.angle += app.angle_delta;
+        app.update_data_array();
+    }
+
+    raster::draw_frame(size, app.background, &app.layers(), &app.vertecies)
+}
+
+fn read_png(path: &Path) -> Option<Image> {
+    let decoder = png::Decoder::new(File::open(path).ok()?);
+    let mut reader = decoder.read_info().ok()?;
+    let mut data = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut data).ok()?;
+    assert_eq!(
+        (info.color_type, info.bit_depth),
+        (png::ColorType::Rgba, png::BitDepth::Eight),
+        "{} must be 8-bit RGBA",
+        path.display()
+    );
+
+    Some(Image {
+        width: info.width,
+        height: info.height,
+        pixels: data[..info.buffer_size()]
+            .chunks_exact(4)
+            .map(|pixel| [pixel[0], pixel[1], pixel[2], pixel[3]])
+            .collect(),
+    })
+}
+
+fn write_png(path: &Path, image: &Image) {
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    let file = BufWriter::new(File::create(path).unwrap());
+    let mut encoder = png::Encoder::new(file, image.width, image.height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder
+        .write_header()
+        .unwrap()
+        .write_image_data(&image.pixels.concat())
+        .unwrap();
+}
+
+// Compares against tests/golden/<name>.png. Run with UPDATE_GOLDEN=1 to
+// (re)generate the golden images after an intended rendering change.
+fn assert_golden(name: &str, actual: &Image) {
+    let golden_path = PathBuf::from(GOLDEN_DIRECTORY).join(format!("{}.png", name));
+    if env::var_os("UPDATE_GOLDEN").is_some() {
+        write_png(&golden_path, actual);
+        return;
+    }
+
+    let expected = read_png(&golden_path).unwrap_or_else(|| {
+        panic!(
+            "missing golden image {}, run with UPDATE_GOLDEN=1 to create it",
+            golden_path.display()
+        )
+    });
+    assert_eq!(
+        (expected.width, expected.height),
+        (actual.width, actual.height),
+        "{}: image size differs from golden",
+        name
+    );
+
+    let mut diff = Image::new(actual.width, actual.height, sfml::graphics::Color::BLACK);
+    let mut mismatched = 0;
+    for (i, (expected, actual)) in expected.pixels.iter().zip(&actual.pixels).enumerate() {
+        let delta = expected
+            .iter()
+            .zip(actual)
+            .map(|(expected, actual)| expected.abs_diff(*actual))
+            .max()
+            .unwrap();
+
+        diff.pixels[i] = if delta > CHANNEL_TOLERANCE {
+            mismatched += 1;
+            [255, 0, 0, 255]
+        } else {
+            let gray = (expected[0] as u16 + expected[1] as u16 + expected[2] as u16) / 6;
+            [gray as u8, gray as u8, gray as u8, 255]
+        };
+    }
+
+    let ratio = mismatched as f32 / actual.pixels.len() as f32;
+    if ratio > MISMATCH_TOLERANCE {
+        let actual_path = PathBuf::from(DIFF_DIRECTORY).join(format!("{}-actual.png", name));
+        let diff_path = PathBuf::from(DIFF_DIRECTORY).join(format!("{}-diff.png", name));
+        write_png(&actual_path, actual);
+        write_png(&diff_path, &diff);
+        panic!(
+            "{}: {} pixels ({:.3}%) differ from golden, see {} and {}",
+            name,
+            mismatched,
+            ratio * 100.0,
+            actual_path.display(),
+            diff_path.display()
+        );
+    }
+}
+
+#[test]
+fn circle() {
+    assert_golden("circle", &render_full(App::new(), (200, 200).into()));
+}
+
+#[test]
+fn rose_4_5() {
+    assert_golden("rose_4_5", &render_full(rose_app(4, 5), (256, 256).into()));
+}
+
+#[test]
+fn rose_3_1_wide() {
+    assert_golden(
+        "rose_3_1_wide",
+        &render_full(rose_app(3, 1), (320, 240).into()),
+    );
+}
+
+#[test]
+fn rose_4_5_tail() {
+    assert_golden(
+        "rose_4_5_tail",
+        &render_tail(rose_app(4, 5), (256, 256).into(), 300, 600.0),
+    );
+}
+
+#[test]
+fn limacon_curve_file() {
+    let config = Config::from_text("limacon.curve", include_str!("../curves/limacon.curve"));
+    let mut app = App::new();
+    app.load_curve(CurveDefinition::parse(&config).unwrap());
+    assert_golden("limacon_curve_file", &render_full(app, (256, 256).into()));
+}
+
+#[test]
+fn limacon_box_fit() {
+    let mut app = curve_app("fit.curve", "r = 1 + 2 * cos(t)\n");
+    app.axis.fit = Fit::Box;
+    app.axis.margin = 20.0;
+    assert_golden("limacon_box_fit", &render_full(app, (256, 256).into()));
+}
+
+#[test]
+fn lemniscate_gaps() {
+    let app = curve_app("lemniscate.curve", "r = sqrt(cos(2 * t))\n");
+    assert_golden("lemniscate_gaps", &render_full(app, (256, 256).into()));
+}
+
+#[test]
+fn rose_2_1_negative_color() {
+    let mut app = rose_app(2, 1);
+    app.axis.negative = NegativeRadius::Color;
+    assert_golden(
+        "rose_2_1_negative_color",
+        &render_full(app, (256, 256).into()),
+    );
+}
+
+#[test]
+fn limacon_fill_evenodd() {
+    let mut app = curve_app("fill.curve", "r = 1 + 2 * cos(t)\n");
+    app.axis.fit = Fit::Box;
+    app.flags.insert(Flags::FILL);
+    app.fill.rule = FillRule::EvenOdd;
+    assert_golden("limacon_fill_evenodd", &render_full(app, (256, 256).into()));
+}
+
+#[test]
+fn cardioid_between_circle() {
+    let mut app = curve_app("between.curve", include_str!("../curves/between.curve"));
+    app.axis.fit = Fit::Box;
+    assert_golden(
+        "cardioid_between_circle",
+        &render_full(app, (256, 256).into()),
+    );
+}
+
+#[test]
+fn circle_intersect_cardioid() {
+    let mut app = curve_app("intersect.curve", include_str!("../curves/intersect.curve"));
+    app.axis.fit = Fit::Box;
+    assert_golden(
+        "circle_intersect_cardioid",
+        &render_full(app, (256, 256).into()),
+    );
+}
+
+#[test]
+fn cardioid_riemann_sum() {
+    let mut app = curve_app("cardioid.curve", "r = 1 + cos(t)\n");
+    app.axis.fit = Fit::Box;
+    app.flags.insert(Flags::RIEMANN);
+    app.riemann.set_target(12);
+    app.riemann.n = 12;
+    assert_golden("cardioid_riemann_sum", &render_full(app, (256, 256).into()));
+}
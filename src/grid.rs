@@ -0,0 +1,174 @@
+use sfml::graphics::{
+    Color, FloatRect, Font, PrimitiveType, RenderStates, RenderTarget, Text, Transformable, Vertex,
+};
+use sfml::system::{Vector2f, Vector2u};
+
+use crate::rose;
+use crate::svg::Svg;
+
+pub struct Cell {
+    pub n: u8,
+    pub d: u8,
+    pub bounds: FloatRect,
+    pub vertecies: Vec<Vertex>,
+}
+
+pub struct RoseGrid {
+    pub n_max: u8,
+    pub d_max: u8,
+    pub color: Color,
+    pub line_color: Color,
+    pub label_size: u32,
+
+    pub cells: Vec<Cell>,
+    lines: Vec<Vertex>,
+    size: Vector2u,
+    angle_delta: f32,
+}
+
+impl RoseGrid {
+    pub fn new(n_max: u8, d_max: u8) -> RoseGrid {
+        RoseGrid {
+            n_max,
+            d_max,
+            color: Color::WHITE,
+            line_color: Color::rgba(255, 255, 255, 48),
+            label_size: 14,
+            cells: Vec::new(),
+            lines: Vec::new(),
+            size: (0, 0).into(),
+            angle_delta: 0.0,
+        }
+    }
+
+    pub fn update(&mut self, size: Vector2u, angle_delta: f32) {
+        if self.size != size
+            || self.angle_delta != angle_delta
+            || self.cells.len() != self.n_max as usize * self.d_max as usize
+        {
+            self.rebuild(size, angle_delta);
+        }
+    }
+
+    pub fn rebuild(&mut self, size: Vector2u, angle_delta: f32) {
+        self.size = size;
+        self.angle_delta = angle_delta;
+        self.cells.clear();
+        self.lines.clear();
+
+        if self.n_max == 0 || self.d_max == 0 {
+            return;
+        }
+
+        let step = if angle_delta > 0.0 { angle_delta } else { 1.0 };
+        let cell_width = size.x as f32 / self.d_max as f32;
+        let cell_height = size.y as f32 / self.n_max as f32;
+        let radius = cell_width.min(cell_height) / 2.0 * 0.8;
+
+        for n in 1..=self.n_max {
+            for d in 1..=self.d_max {
+                let bounds = FloatRect::new(
+                    (d - 1) as f32 * cell_width,
+                    (n - 1) as f32 * cell_height,
+                    cell_width,
+                    cell_height,
+                );
+                let center_x = bounds.left + cell_width / 2.0;
+                let center_y = bounds.top + cell_height / 2.0;
+
+                let k = n as f32 / d as f32;
+                let angle_limit = rose::angle_limit(n, d);
+                let count = (angle_limit / step).ceil() as usize + 1;
+
+                let vertecies = (0..count)
+                    .map(|i| {
+                        let mut point = Vector2f::default();
+                        rose::angle_to_point(&mut point, (i as f32 * step).min(angle_limit), k);
+                        Vertex::with_pos_color(
+                            (point.x * radius + center_x, -point.y * radius + center_y).into(),
+                            self.color,
+                        )
+                    })
+                    .collect();
+
+                self.cells.push(Cell {
+                    n,
+                    d,
+                    bounds,
+                    vertecies,
+                });
+            }
+        }
+
+        for d in 1..self.d_max {
+            let x = d as f32 * cell_width;
+            self.lines
+                .push(Vertex::with_pos_color((x, 0.0).into(), self.line_color));
+            self.lines.push(Vertex::with_pos_color(
+                (x, size.y as f32).into(),
+                self.line_color,
+            ));
+        }
+        for n in 1..self.n_max {
+            let y = n as f32 * cell_height;
+            self.lines
+                .push(Vertex::with_pos_color((0.0, y).into(), self.line_color));
+            self.lines.push(Vertex::with_pos_color(
+                (size.x as f32, y).into(),
+                self.line_color,
+            ));
+        }
+    }
+
+    pub fn draw(
+        &self,
+        render_target: &mut dyn RenderTarget,
+        background: Color,
+        font: Option<&Font>,
+        labels: bool,
+    ) {
+        render_target.clear(background);
+        render_target.draw_primitives(&self.lines, PrimitiveType::LINES, &RenderStates::DEFAULT);
+
+        for cell in self.cells.iter() {
+            render_target.draw_primitives(
+                &cell.vertecies,
+                PrimitiveType::LINE_STRIP,
+                &RenderStates::DEFAULT,
+            );
+
+            if let (true, Some(font)) = (labels, font) {
+                let mut label = Text::new(&Self::label(cell), font, self.label_size);
+                label.set_fill_color(self.color);
+                label.set_outline_color(background);
+                label.set_outline_thickness(1.5);
+                label.set_position((cell.bounds.left + 4.0, cell.bounds.top + 2.0));
+                render_target.draw(&label);
+            }
+        }
+    }
+
+    pub fn draw_to_svg(&self, svg: &mut Svg, labels: bool) {
+        for line in self.lines.chunks(2) {
+            svg.polyline(line, self.line_color, 1.0);
+        }
+
+        for cell in self.cells.iter() {
+            svg.polyline(&cell.vertecies, self.color, 1.0);
+
+            if labels {
+                svg.text(
+                    cell.bounds.left + 4.0,
+                    cell.bounds.top + 2.0 + self.label_size as f32,
+                    self.label_size,
+                    self.color,
+                    &Self::label(cell),
+                );
+            }
+        }
+    }
+
+    fn label(cell: &Cell) -> String {
+        format!("{}/{}", cell.n, cell.d)
+    }
+}
@@ -0,0 +1,408 @@
+use std::fmt::Write;
+
+use sfml::graphics::{Color, Font, RenderTarget, Text, Transformable, Vertex};
+use sfml::system::{Vector2f, Vector2u};
+
+use crate::radial_axis::RadialAxis;
+use crate::sample_export::{json_number, json_string};
+use crate::svg::Svg;
+use crate::viewer::AngleToPoint;
+
+// Degrees between the samples of either curve
+const STEP: f32 = 0.5;
+// Degrees on either side for the derivatives of Newton's method
+const DERIVATIVE_STEP: f32 = 0.05;
+const NEWTON_STEPS: usize = 8;
+// Shares of the largest radius: below the first a curve passes through the
+// pole, points closer than the second are the same point
+const POLE_TOLERANCE: f32 = 1e-4;
+const DUPLICATE_TOLERANCE: f32 = 1e-3;
+
+const MARK_SIZE: f32 = 5.0;
+const LABEL_SIZE: u32 = 12;
+const MARK_COLOR: Color = Color::rgb(255, 230, 80);
+
+pub struct Intersection {
+    pub point: Vector2f,
+    // Angles in degrees at which the first and the second curve pass the
+    // point, they differ when a curve gets there with a negative radius or
+    // when both pass through the pole
+    pub angles: (f32, f32),
+}
+
+impl Intersection {
+    pub fn at_pole(&self) -> bool {
+        self.point == Vector2f::default()
+    }
+
+    pub fn label(&self) -> String {
+        // Rounds -0.0004 to 0.000 rather than -0.000
+        let round = |value: f32| (value * 1000.0).round() / 1000.0 + 0.0;
+        format!("({:.3}, {:.3})", round(self.point.x), round(self.point.y))
+    }
+
+    // The point is where the curves cross on screen, its distance from the
+    // pole stands in for the radius
+    fn to_screen(&self, axis: &RadialAxis, size: Vector2u) -> Vector2f {
+        let r = self.point.x.hypot(self.point.y);
+        axis.to_screen(self.point, r, self.angles.0, size)
+    }
+}
+
+struct Sample {
+    angle: f32,
+    r: f32,
+    point: Vector2f,
+}
+
+fn sample(angle_to_point: &dyn Fn(&mut Vector2f, f32) -> f32, angle_limit: f32) -> Vec<Sample> {
+    let count = (angle_limit / STEP).ceil().max(1.0) as usize;
+    (0..=count)
+        .map(|i| {
+            let angle = angle_limit * i as f32 / count as f32;
+            let mut point = Vector2f::default();
+            let r = angle_to_point(&mut point, angle);
+            Sample { angle, r, point }
+        })
+        .collect()
+}
+
+// The second curve of the [intersect] section and the points where the
+// curve meets it over both periods
+pub struct Intersections {
+    pub angle_to_point: AngleToPoint,
+    pub angle_limit: f32,
+    pub color: Color,
+    pub points: Vec<Intersection>,
+}
+
+impl Intersections {
+    pub fn new(angle_to_point: AngleToPoint, angle_limit: f32, color: Color) -> Intersections {
+        Intersections {
+            angle_to_point,
+            angle_limit,
+            color,
+            points: Vec::new(),
+        }
+    }
+
+    // Crossings of the sampled polylines refined by Newton's method, and
+    // the pole when both curves reach it, at whatever angles they do
+    pub fn find(&mut self, angle_to_point: &dyn Fn(&mut Vector2f, f32) -> f32, angle_limit: f32) {
+        let first = sample(angle_to_point, angle_limit);
+        let second = sample(&self.angle_to_point, self.angle_limit);
+        let scale = first
+            .iter()
+            .chain(&second)
+            .map(|sample| sample.r.abs())
+            .filter(|r| r.is_finite())
+            .fold(f32::MIN_POSITIVE, f32::max);
+
+        self.points.clear();
+        if let (Some(first_angle), Some(second_angle)) = (
+            pole_angle(angle_to_point, &first, scale),
+            pole_angle(&self.angle_to_point, &second, scale),
+        ) {
+            self.points.push(Intersection {
+                point: Vector2f::default(),
+                angles: (first_angle, second_angle),
+            });
+        }
+
+        for pair in first.windows(2) {
+            for other in second.windows(2) {
+                let (s, t, point) =
+                    match cross(pair[0].point, pair[1].point, other[0].point, other[1].point) {
+                        Some(crossing) => crossing,
+                        None => continue,
+                    };
+                let angles = (
+                    pair[0].angle + (pair[1].angle - pair[0].angle) * s,
+                    other[0].angle + (other[1].angle - other[0].angle) * t,
+                );
+                let found = refine(
+                    angle_to_point,
+                    &self.angle_to_point,
+                    angles,
+                    DUPLICATE_TOLERANCE * scale,
+                )
+                .unwrap_or(Intersection { point, angles });
+
+                // Curves traced more than once per period cross in the same place again
+                let distance = |other: &Intersection| {
+                    let offset = other.point - found.point;
+                    offset.x.hypot(offset.y)
+                };
+                if self
+                    .points
+                    .iter()
+                    .all(|other| distance(other) > DUPLICATE_TOLERANCE * scale)
+                {
+                    self.points.push(found);
+                }
+            }
+        }
+    }
+
+    // The second curve and a cross on every intersection as pairs of line ends
+    pub fn vertecies(&self, axis: &RadialAxis, size: Vector2u) -> Vec<Vertex> {
+        let defined = |point: &Vector2f| point.x.is_finite() && point.y.is_finite();
+        let curve: Vec<Vector2f> = sample(&self.angle_to_point, self.angle_limit)
+            .iter()
+            .map(|sample| axis.to_screen(sample.point, sample.r, sample.angle, size))
+            .collect();
+
+        let mut lines = Vec::new();
+        for pair in curve.windows(2) {
+            if pair.iter().all(defined) {
+                lines.push(Vertex::with_pos_color(pair[0], self.color));
+                lines.push(Vertex::with_pos_color(pair[1], self.color));
+            }
+        }
+        for intersection in &self.points {
+            let center = intersection.to_screen(axis, size);
+            for (x, y) in [(1.0, 1.0), (1.0, -1.0)] {
+                let corner = Vector2f::new(x, y) * MARK_SIZE;
+                lines.push(Vertex::with_pos_color(center - corner, MARK_COLOR));
+                lines.push(Vertex::with_pos_color(center + corner, MARK_COLOR));
+            }
+        }
+        lines
+    }
+
+    // Coordinates above and to the right of every mark
+    pub fn draw_labels(
+        &self,
+        target: &mut dyn RenderTarget,
+        font: &Font,
+        axis: &RadialAxis,
+        size: Vector2u,
+        background: Color,
+    ) {
+        for intersection in &self.points {
+            let position = intersection.to_screen(axis, size)
+                + Vector2f::new(MARK_SIZE + 2.0, -(MARK_SIZE + LABEL_SIZE as f32 + 2.0));
+            let mut label = Text::new(&intersection.label(), font, LABEL_SIZE);
+            label.set_fill_color(MARK_COLOR);
+            label.set_outline_color(background);
+            label.set_outline_thickness(1.5);
+            label.set_position(position);
+            target.draw(&label);
+        }
+    }
+
+    pub fn draw_to_svg(&self, svg: &mut Svg, axis: &RadialAxis, size: Vector2u) {
+        // One polyline per defined run of the second curve
+        let mut run = Vec::new();
+        for sample in sample(&self.angle_to_point, self.angle_limit) {
+            let point = axis.to_screen(sample.point, sample.r, sample.angle, size);
+            if point.x.is_finite() && point.y.is_finite() {
+                run.push(Vertex::with_pos_color(point, self.color));
+            } else {
+                svg.polyline(&run, self.color, 1.0);
+                run.clear();
+            }
+        }
+        svg.polyline(&run, self.color, 1.0);
+
+        for intersection in &self.points {
+            let center = intersection.to_screen(axis, size);
+            for (x, y) in [(1.0, 1.0), (1.0, -1.0)] {
+                let corner = Vector2f::new(x, y) * MARK_SIZE;
+                svg.polyline(
+                    &[
+                        Vertex::with_pos_color(center - corner, MARK_COLOR),
+                        Vertex::with_pos_color(center + corner, MARK_COLOR),
+                    ],
+                    MARK_COLOR,
+                    1.0,
+                );
+            }
+            svg.text(
+                center.x + MARK_SIZE + 2.0,
+                center.y - MARK_SIZE - 2.0,
+                LABEL_SIZE,
+                MARK_COLOR,
+                &intersection.label(),
+            );
+        }
+    }
+
+    pub fn to_json(&self, curve_name: &str) -> String {
+        let mut text = String::new();
+        writeln!(text, "{{").unwrap();
+        writeln!(text, "  \"curve\": {},", json_string(curve_name)).unwrap();
+        writeln!(text, "  \"intersections\": [").unwrap();
+        for (i, intersection) in self.points.iter().enumerate() {
+            let point = intersection.point;
+            let angle = point.y.atan2(point.x).to_degrees().rem_euclid(360.0);
+            writeln!(
+                text,
+                "    {{\"x\": {}, \"y\": {}, \"r\": {}, \"angle\": {}, \"first_angle\": {}, \"second_angle\": {}, \"pole\": {}}}{}",
+                json_number(point.x, 6),
+                json_number(point.y, 6),
+                json_number(point.x.hypot(point.y), 6),
+                json_number(angle, 4),
+                json_number(intersection.angles.0, 4),
+                json_number(intersection.angles.1, 4),
+                intersection.at_pole(),
+                if i + 1 < self.points.len() { "," } else { "" }
+            )
+            .unwrap();
+        }
+        writeln!(text, "  ]").unwrap();
+        writeln!(text, "}}").unwrap();
+        text
+    }
+}
+
+// Where the segments p1-p2 and q1-q2 cross, as the share of the way along
+// each and the point itself
+fn cross(p1: Vector2f, p2: Vector2f, q1: Vector2f, q2: Vector2f) -> Option<(f32, f32, Vector2f)> {
+    let (p, q, d) = (p2 - p1, q2 - q1, q1 - p1);
+    let denominator = p.x * q.y - p.y * q.x;
+    if denominator == 0.0 {
+        return None;
+    }
+    let s = (d.x * q.y - d.y * q.x) / denominator;
+    let t = (d.x * p.y - d.y * p.x) / denominator;
+    if (0.0..=1.0).contains(&s) && (0.0..=1.0).contains(&t) {
+        Some((s, t, p1 + p * s))
+    } else {
+        None
+    }
+}
+
+// Solves first(a) = second(b) from the angles of a polyline crossing, giving
+// up where the curves are nearly tangent and the method runs off
+fn refine(
+    first: &dyn Fn(&mut Vector2f, f32) -> f32,
+    second: &dyn Fn(&mut Vector2f, f32) -> f32,
+    angles: (f32, f32),
+    tolerance: f32,
+) -> Option<Intersection> {
+    let at = |angle_to_point: &dyn Fn(&mut Vector2f, f32) -> f32, angle: f32| {
+        let mut point = Vector2f::default();
+        angle_to_point(&mut point, angle);
+        point
+    };
+    let derivative = |angle_to_point: &dyn Fn(&mut Vector2f, f32) -> f32, angle: f32| {
+        (at(angle_to_point, angle + DERIVATIVE_STEP) - at(angle_to_point, angle - DERIVATIVE_STEP))
+            / (2.0 * DERIVATIVE_STEP)
+    };
+
+    let (mut a, mut b) = angles;
+    for _ in 0..NEWTON_STEPS {
+        let d = at(first, a) - at(second, b);
+        let (p, q) = (derivative(first, a), derivative(second, b));
+        let determinant = q.x * p.y - p.x * q.y;
+        let da = (d.x * q.y - q.x * d.y) / determinant;
+        let db = (d.x * p.y - p.x * d.y) / determinant;
+        if !(da.is_finite() && db.is_finite()) {
+            return None;
+        }
+        a += da;
+        b += db;
+    }
+
+    let point = at(first, a);
+    let miss = point - at(second, b);
+    if (a - angles.0).abs() > STEP
+        || (b - angles.1).abs() > STEP
+        || miss.x.hypot(miss.y) > tolerance
+    {
+        return None;
+    }
+    Some(Intersection {
+        point,
+        angles: (a, b),
+    })
+}
+
+// First angle at which the curve passes through the pole, found by a ternary
+// search for the zero of |r| around every local minimum of the samples
+fn pole_angle(
+    angle_to_point: &dyn Fn(&mut Vector2f, f32) -> f32,
+    samples: &[Sample],
+    scale: f32,
+) -> Option<f32> {
+    let radius = |angle: f32| angle_to_point(&mut Vector2f::default(), angle).abs();
+    (0..samples.len()).find_map(|i| {
+        let before = &samples[i.saturating_sub(1)];
+        let after = &samples[(i + 1).min(samples.len() - 1)];
+        let r = samples[i].r.abs();
+        if !(r <= before.r.abs() && r <= after.r.abs()) {
+            return None;
+        }
+
+        let (mut low, mut high) = (before.angle, after.angle);
+        for _ in 0..40 {
+            let third = (high - low) / 3.0;
+            if radius(low + third) < radius(high - third) {
+                high -= third;
+            } else {
+                low += third;
+            }
+        }
+        let angle = (low + high) / 2.0;
+        (radius(angle) <= POLE_TOLERANCE * scale).then_some(angle)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curve_file::CurveDefinition;
+
+    fn intersections(
+        first: impl Fn(f32) -> f32,
+        second: impl Fn(f32) -> f32 + 'static,
+        second_limit: f32,
+    ) -> Vec<Intersection> {
+        let mut intersections = Intersections::new(
+            Box::new(move |point, angle| {
+                CurveDefinition::radius_to_point(point, angle, second(angle.to_radians()))
+            }),
+            second_limit,
+            Color::WHITE,
+        );
+        intersections.find(
+            &|point, angle| {
+                CurveDefinition::radius_to_point(point, angle, first(angle.to_radians()))
+            },
+            360.0,
+        );
+        intersections.points
+    }
+
+    #[test]
+    fn rose_meets_circle_at_negative_radii() {
+        // cos(2t) = 1/2 at four angles, and cos(2t) = -1/2 at four more
+        // where the rose is on the far side of the origin
+        let points = intersections(|t| (2.0 * t).cos(), |_| 0.5, 360.0);
+        assert_eq!(points.len(), 8);
+        for intersection in &points {
+            let r = intersection.point.x.hypot(intersection.point.y);
+            assert!((r - 0.5).abs() < 1e-4, "{}", intersection.label());
+        }
+        assert!(points.iter().any(|intersection| {
+            let (first, second) = intersection.angles;
+            (first - 60.0).abs() < 1e-2 && (second - 240.0).abs() < 1e-2
+        }));
+    }
+
+    #[test]
+    fn cardioid_meets_circle_at_the_pole() {
+        // 1 + cos(t) = 3 cos(t) at t = 60 and 300 degrees, the cardioid reaches
+        // the pole at 180 degrees and the circle at 90
+        let mut points = intersections(|t| 1.0 + t.cos(), |t| 3.0 * t.cos(), 180.0);
+        assert_eq!(points.len(), 3);
+        let pole = points.remove(0);
+        assert!(pole.at_pole());
+        assert!((pole.angles.0 - 180.0).abs() < 0.1 && (pole.angles.1 - 90.0).abs() < 0.1);
+
+        let mut labels: Vec<String> = points.iter().map(Intersection::label).collect();
+        labels.sort();
+        assert_eq!(labels, ["(0.750, -1.299)", "(0.750, 1.299)"]);
+    }
+}
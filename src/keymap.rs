@@ -0,0 +1,531 @@
+use sfml::window::Key;
+
+use crate::config::Config;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    Close,
+    Pause,
+    SaveFrame,
+    ToggleHelp,
+    ToggleDebug,
+    Reinit,
+    RecreateWindow,
+    Fullscreen,
+    ToggleCursor,
+    SaveAnimation,
+    ExportSamples,
+    ExportStats,
+    ExportIntersections,
+    Clear,
+    SkipCycle,
+    ToggleCutoff,
+    ToggleFreeze,
+    ToggleGrid,
+    ToggleGridLabels,
+    ToggleRadar,
+    ToggleWindRose,
+    ToggleSpiderChart,
+    ToggleTracing,
+    ToggleFill,
+    CycleFillRule,
+    ToggleRiemannSum,
+    CycleRiemannRule,
+    RiemannSectorsUp,
+    RiemannSectorsDown,
+    NextPlugin,
+    PreviousPlugin,
+    FpsDown,
+    FpsUp,
+    CycleFramePacing,
+    CycleNegativeRadius,
+    LengthUp,
+    LengthDown,
+    AngleDeltaUp,
+    AngleDeltaDown,
+    AntialiasingUp,
+    AntialiasingDown,
+}
+
+impl Action {
+    pub const ALL: [Action; 41] = [
+        Action::Close,
+        Action::Pause,
+        Action::SaveFrame,
+        Action::ToggleHelp,
+        Action::ToggleDebug,
+        Action::Reinit,
+        Action::RecreateWindow,
+        Action::Fullscreen,
+        Action::ToggleCursor,
+        Action::SaveAnimation,
+        Action::ExportSamples,
+        Action::ExportStats,
+        Action::ExportIntersections,
+        Action::Clear,
+        Action::SkipCycle,
+        Action::ToggleCutoff,
+        Action::ToggleFreeze,
+        Action::ToggleGrid,
+        Action::ToggleGridLabels,
+        Action::ToggleRadar,
+        Action::ToggleWindRose,
+        Action::ToggleSpiderChart,
+        Action::ToggleTracing,
+        Action::ToggleFill,
+        Action::CycleFillRule,
+        Action::ToggleRiemannSum,
+        Action::CycleRiemannRule,
+        Action::RiemannSectorsUp,
+        Action::RiemannSectorsDown,
+        Action::NextPlugin,
+        Action::PreviousPlugin,
+        Action::FpsDown,
+        Action::FpsUp,
+        Action::CycleFramePacing,
+        Action::CycleNegativeRadius,
+        Action::LengthUp,
+        Action::LengthDown,
+        Action::AngleDeltaUp,
+        Action::AngleDeltaDown,
+        Action::AntialiasingUp,
+        Action::AntialiasingDown,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Close => "close",
+            Action::Pause => "pause",
+            Action::SaveFrame => "save_frame",
+            Action::ToggleHelp => "toggle_help",
+            Action::ToggleDebug => "toggle_debug",
+            Action::Reinit => "reinit",
+            Action::RecreateWindow => "recreate_window",
+            Action::Fullscreen => "fullscreen",
+            Action::ToggleCursor => "toggle_cursor",
+            Action::SaveAnimation => "save_animation",
+            Action::ExportSamples => "export_samples",
+            Action::ExportStats => "export_stats",
+            Action::ExportIntersections => "export_intersections",
+            Action::Clear => "clear",
+            Action::SkipCycle => "skip_cycle",
+            Action::ToggleCutoff => "toggle_cutoff",
+            Action::ToggleFreeze => "toggle_freeze",
+            Action::ToggleGrid => "toggle_grid",
+            Action::ToggleGridLabels => "toggle_grid_labels",
+            Action::ToggleRadar => "toggle_radar",
+            Action::ToggleWindRose => "toggle_wind_rose",
+            Action::ToggleSpiderChart => "toggle_spider_chart",
+            Action::ToggleTracing => "toggle_tracing",
+            Action::ToggleFill => "toggle_fill",
+            Action::CycleFillRule => "cycle_fill_rule",
+            Action::ToggleRiemannSum => "toggle_riemann_sum",
+            Action::CycleRiemannRule => "cycle_riemann_rule",
+            Action::RiemannSectorsUp => "riemann_sectors_up",
+            Action::RiemannSectorsDown => "riemann_sectors_down",
+            Action::NextPlugin => "next_plugin",
+            Action::PreviousPlugin => "previous_plugin",
+            Action::FpsDown => "fps_down",
+            Action::FpsUp => "fps_up",
+            Action::CycleFramePacing => "cycle_frame_pacing",
+            Action::CycleNegativeRadius => "cycle_negative_radius",
+            Action::LengthUp => "length_up",
+            Action::LengthDown => "length_down",
+            Action::AngleDeltaUp => "angle_delta_up",
+            Action::AngleDeltaDown => "angle_delta_down",
+            Action::AntialiasingUp => "antialiasing_up",
+            Action::AntialiasingDown => "antialiasing_down",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Action::Close => "Close window (exit)",
+            Action::Pause => "Pause",
+            Action::SaveFrame => "Save finished graph as PNG and SVG",
+            Action::ToggleHelp => "Show this help",
+            Action::ToggleDebug => "Debug screen",
+            Action::Reinit => "Re-init program",
+            Action::RecreateWindow => "Recreate window",
+            Action::Fullscreen => "Fullscreen",
+            Action::ToggleCursor => "Hide cursor",
+            Action::SaveAnimation => "Save animation as series of PNG frames",
+            Action::ExportSamples => "Export samples of one period as CSV/JSON",
+            Action::ExportStats => "Print arc length, area and bounds, export as JSON",
+            Action::ExportIntersections => {
+                "Print intersections with the second curve, export as JSON"
+            }
+            Action::Clear => "Clear window",
+            Action::SkipCycle => "Skip first animation cycle",
+            Action::ToggleCutoff => "Toggle cutoff/tail fade",
+            Action::ToggleFreeze => "Freeze stream (keep reading, drop new records)",
+            Action::ToggleGrid => "Toggle table of roses",
+            Action::ToggleGridLabels => "Toggle table labels",
+            Action::ToggleRadar => "Toggle radar PPI display",
+            Action::ToggleWindRose => "Toggle wind rose chart",
+            Action::ToggleSpiderChart => "Toggle spider chart",
+            Action::ToggleTracing => "Toggle tangent, normal and osculating circle",
+            Action::ToggleFill => "Toggle filled region",
+            Action::CycleFillRule => "Cycle fill rule (nonzero, evenodd)",
+            Action::ToggleRiemannSum => "Toggle polar Riemann sum",
+            Action::CycleRiemannRule => "Cycle Riemann sum rule (left, right, mid)",
+            Action::RiemannSectorsUp => "More Riemann sum sectors by 1 (Shift: x10)",
+            Action::RiemannSectorsDown => "Fewer Riemann sum sectors by 1 (Shift: x10)",
+            Action::NextPlugin => "Switch to next curve plugin",
+            Action::PreviousPlugin => "Switch to previous curve plugin",
+            Action::FpsDown => "Decrease FPS limit by 10 (1 with Alt)",
+            Action::FpsUp => "Increase FPS limit by 10 (1 with Alt)",
+            Action::CycleFramePacing => "Cycle frame pacing (capped, uncapped, vsync)",
+            Action::CycleNegativeRadius => "Cycle negative radius display",
+            Action::LengthUp => "Increase line length by 1 link (Shift: x10)",
+            Action::LengthDown => "Decrease line length by 1 link (Shift: x10)",
+            Action::AngleDeltaUp => "Increase angle delta by 0.1 degree (Shift: x10)",
+            Action::AngleDeltaDown => "Decrease angle delta by 0.1 degree (Shift: x10)",
+            Action::AntialiasingUp => "Increase antialiasing level",
+            Action::AntialiasingDown => "Decrease antialiasing level",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Action> {
+        Self::ALL.into_iter().find(|action| action.name() == name)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Binding {
+    pub code: Key,
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+}
+
+impl Binding {
+    pub const fn new(code: Key) -> Binding {
+        Binding {
+            code,
+            ctrl: false,
+            shift: false,
+            alt: false,
+        }
+    }
+
+    pub const fn ctrl(mut self) -> Binding {
+        self.ctrl = true;
+        self
+    }
+
+    pub const fn shift(mut self) -> Binding {
+        self.shift = true;
+        self
+    }
+
+    pub fn parse(text: &str) -> Option<Binding> {
+        let mut parts: Vec<&str> = text.split('+').map(str::trim).collect();
+        let name = parts.pop()?;
+        let mut binding = Binding::new(KEYS.into_iter().find(|key| format!("{:?}", key) == name)?);
+
+        for modifier in parts {
+            match modifier {
+                "Ctrl" => binding.ctrl = true,
+                "Shift" => binding.shift = true,
+                "Alt" => binding.alt = true,
+                _ => return None,
+            }
+        }
+
+        Some(binding)
+    }
+
+    fn modifier_count(&self) -> usize {
+        self.ctrl as usize + self.shift as usize + self.alt as usize
+    }
+
+    fn matches(&self, code: Key, ctrl: bool, shift: bool, alt: bool) -> bool {
+        self.code == code && (ctrl || !self.ctrl) && (shift || !self.shift) && (alt || !self.alt)
+    }
+}
+
+impl std::fmt::Display for Binding {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if self.ctrl {
+            write!(f, "Ctrl+")?;
+        }
+        if self.shift {
+            write!(f, "Shift+")?;
+        }
+        if self.alt {
+            write!(f, "Alt+")?;
+        }
+        write!(f, "{:?}", self.code)
+    }
+}
+
+pub struct Keymap {
+    pub bindings: Vec<(Binding, Action)>,
+}
+
+impl Keymap {
+    pub fn load(&mut self, config: &Config) {
+        for (name, keys) in config.section("keys") {
+            let action = match Action::from_name(name) {
+                Some(action) => action,
+                None => {
+                    eprintln!("{}: unknown action \"{}\"", config.path, name);
+                    continue;
+                }
+            };
+
+            let bindings: Option<Vec<Binding>> = keys
+                .split(',')
+                .map(str::trim)
+                .filter(|key| !key.is_empty())
+                .map(Binding::parse)
+                .collect();
+            match bindings {
+                Some(bindings) => {
+                    self.bindings.retain(|(_, bound)| *bound != action);
+                    self.bindings
+                        .extend(bindings.into_iter().map(|binding| (binding, action)));
+                }
+                None => eprintln!(
+                    "{}: invalid key list \"{}\" for {}",
+                    config.path, keys, name
+                ),
+            }
+        }
+    }
+
+    pub fn action(&self, code: Key, ctrl: bool, shift: bool, alt: bool) -> Option<Action> {
+        self.bindings
+            .iter()
+            .filter(|(binding, _)| binding.matches(code, ctrl, shift, alt))
+            .max_by_key(|(binding, _)| binding.modifier_count())
+            .map(|(_, action)| *action)
+    }
+
+    // One line per action, filled into `columns` columns top to bottom so
+    // neighbouring actions stay together
+    pub fn help_text(&self, columns: usize) -> String {
+        let entries: Vec<(String, &str)> = Action::ALL
+            .iter()
+            .map(|action| {
+                let keys: Vec<String> = self
+                    .bindings
+                    .iter()
+                    .filter(|(_, bound)| bound == action)
+                    .map(|(binding, _)| binding.to_string())
+                    .collect();
+                (keys.join(", "), action.description())
+            })
+            .collect();
+        let rows = entries.len().div_ceil(columns.max(1));
+
+        let mut lines = vec![String::new(); rows];
+        for column in entries.chunks(rows) {
+            let key_width = column.iter().map(|(keys, _)| keys.len()).max().unwrap_or(0);
+            let cells: Vec<String> = column
+                .iter()
+                .map(|(keys, description)| format!("{:<key_width$} - {}", keys, description))
+                .collect();
+            let width = cells
+                .iter()
+                .map(|cell| cell.chars().count())
+                .max()
+                .unwrap_or(0);
+            for (line, cell) in lines.iter_mut().zip(&cells) {
+                if !line.is_empty() {
+                    line.push_str("   ");
+                }
+                line.push_str(&format!("{:<width$}", cell));
+            }
+        }
+
+        let mut text = String::new();
+        for line in lines {
+            text += line.trim_end();
+            text += "\n";
+        }
+        text
+    }
+}
+
+impl Default for Keymap {
+    fn default() -> Keymap {
+        Keymap {
+            bindings: vec![
+                (Binding::new(Key::Escape), Action::Close),
+                (Binding::new(Key::Space), Action::Pause),
+                (Binding::new(Key::F2), Action::SaveFrame),
+                (Binding::new(Key::F1), Action::ToggleHelp),
+                (Binding::new(Key::F3), Action::ToggleDebug),
+                (Binding::new(Key::F5), Action::Reinit),
+                (Binding::new(Key::F5).shift(), Action::RecreateWindow),
+                (Binding::new(Key::F11), Action::Fullscreen),
+                (Binding::new(Key::F), Action::Fullscreen),
+                (Binding::new(Key::H), Action::ToggleCursor),
+                (Binding::new(Key::G), Action::SaveAnimation),
+                (Binding::new(Key::E), Action::ExportSamples),
+                (Binding::new(Key::E).shift(), Action::ExportStats),
+                (Binding::new(Key::I), Action::ExportIntersections),
+                (Binding::new(Key::C), Action::Clear),
+                (Binding::new(Key::S), Action::SkipCycle),
+                (Binding::new(Key::N), Action::ToggleCutoff),
+                (Binding::new(Key::Z), Action::ToggleFreeze),
+                (Binding::new(Key::M), Action::ToggleGrid),
+                (Binding::new(Key::L), Action::ToggleGridLabels),
+                (Binding::new(Key::R), Action::ToggleRadar),
+                (Binding::new(Key::W), Action::ToggleWindRose),
+                (Binding::new(Key::K), Action::ToggleSpiderChart),
+                (Binding::new(Key::T), Action::ToggleTracing),
+                (Binding::new(Key::B), Action::ToggleFill),
+                (Binding::new(Key::B).shift(), Action::CycleFillRule),
+                (Binding::new(Key::J), Action::ToggleRiemannSum),
+                (Binding::new(Key::J).shift(), Action::CycleRiemannRule),
+                (Binding::new(Key::Up), Action::RiemannSectorsUp),
+                (Binding::new(Key::Down), Action::RiemannSectorsDown),
+                (Binding::new(Key::P), Action::NextPlugin),
+                (Binding::new(Key::P).shift(), Action::PreviousPlugin),
+                // Due to Windows behaviour Shift + Ctrl + 0 is
+                // not being registered thus Alt key is used as
+                // the fine step modifier for FPS limit
+                (Binding::new(Key::Num9).shift(), Action::FpsDown),
+                (Binding::new(Key::Num0).shift(), Action::FpsUp),
+                (Binding::new(Key::V), Action::CycleFramePacing),
+                (Binding::new(Key::O), Action::CycleNegativeRadius),
+                (Binding::new(Key::Add), Action::LengthUp),
+                (Binding::new(Key::Equal), Action::LengthUp),
+                (Binding::new(Key::Subtract), Action::LengthDown),
+                (Binding::new(Key::Hyphen), Action::LengthDown),
+                (Binding::new(Key::Add).ctrl(), Action::AngleDeltaUp),
+                (Binding::new(Key::Equal).ctrl(), Action::AngleDeltaUp),
+                (Binding::new(Key::Subtract).ctrl(), Action::AngleDeltaDown),
+                (Binding::new(Key::Hyphen).ctrl(), Action::AngleDeltaDown),
+                (Binding::new(Key::RBracket), Action::AntialiasingUp),
+                (Binding::new(Key::LBracket), Action::AntialiasingDown),
+            ],
+        }
+    }
+}
+
+const KEYS: [Key; 101] = [
+    Key::A,
+    Key::B,
+    Key::C,
+    Key::D,
+    Key::E,
+    Key::F,
+    Key::G,
+    Key::H,
+    Key::I,
+    Key::J,
+    Key::K,
+    Key::L,
+    Key::M,
+    Key::N,
+    Key::O,
+    Key::P,
+    Key::Q,
+    Key::R,
+    Key::S,
+    Key::T,
+    Key::U,
+    Key::V,
+    Key::W,
+    Key::X,
+    Key::Y,
+    Key::Z,
+    Key::Num0,
+    Key::Num1,
+    Key::Num2,
+    Key::Num3,
+    Key::Num4,
+    Key::Num5,
+    Key::Num6,
+    Key::Num7,
+    Key::Num8,
+    Key::Num9,
+    Key::Escape,
+    Key::LControl,
+    Key::LShift,
+    Key::LAlt,
+    Key::LSystem,
+    Key::RControl,
+    Key::RShift,
+    Key::RAlt,
+    Key::RSystem,
+    Key::Menu,
+    Key::LBracket,
+    Key::RBracket,
+    Key::Semicolon,
+    Key::Comma,
+    Key::Period,
+    Key::Quote,
+    Key::Slash,
+    Key::Backslash,
+    Key::Tilde,
+    Key::Equal,
+    Key::Hyphen,
+    Key::Space,
+    Key::Enter,
+    Key::Backspace,
+    Key::Tab,
+    Key::PageUp,
+    Key::PageDown,
+    Key::End,
+    Key::Home,
+    Key::Insert,
+    Key::Delete,
+    Key::Add,
+    Key::Subtract,
+    Key::Multiply,
+    Key::Divide,
+    Key::Left,
+    Key::Right,
+    Key::Up,
+    Key::Down,
+    Key::Numpad0,
+    Key::Numpad1,
+    Key::Numpad2,
+    Key::Numpad3,
+    Key::Numpad4,
+    Key::Numpad5,
+    Key::Numpad6,
+    Key::Numpad7,
+    Key::Numpad8,
+    Key::Numpad9,
+    Key::F1,
+    Key::F2,
+    Key::F3,
+    Key::F4,
+    Key::F5,
+    Key::F6,
+    Key::F7,
+    Key::F8,
+    Key::F9,
+    Key::F10,
+    Key::F11,
+    Key::F12,
+    Key::F13,
+    Key::F14,
+    Key::F15,
+    Key::Pause,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn help_fills_columns_top_to_bottom() {
+        let keymap = Keymap::default();
+        let single = keymap.help_text(1);
+        assert_eq!(single.lines().count(), Action::ALL.len());
+        assert!(single.starts_with("Escape"));
+
+        let double = keymap.help_text(2);
+        let rows = Action::ALL.len().div_ceil(2);
+        assert_eq!(double.lines().count(), rows);
+        let second = Action::ALL[rows].description();
+        assert!(double.lines().next().unwrap().ends_with(second));
+    }
+}
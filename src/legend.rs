@@ -0,0 +1,95 @@
+use sfml::graphics::{
+    Color, Font, PrimitiveType, RenderStates, RenderTarget, Text, Transformable, Vertex,
+};
+use sfml::system::{Vector2f, Vector2u};
+
+use crate::svg::Svg;
+
+// Distinct colors for unrelated series
+const CATEGORICAL: [Color; 6] = [
+    Color::rgb(31, 119, 180),
+    Color::rgb(255, 127, 14),
+    Color::rgb(44, 160, 44),
+    Color::rgb(214, 39, 40),
+    Color::rgb(148, 103, 189),
+    Color::rgb(23, 190, 207),
+];
+// Cool to warm for ordered classes
+const SEQUENTIAL: [Color; 8] = [
+    Color::rgb(66, 146, 198),
+    Color::rgb(65, 182, 196),
+    Color::rgb(127, 205, 187),
+    Color::rgb(199, 233, 180),
+    Color::rgb(254, 224, 139),
+    Color::rgb(253, 174, 97),
+    Color::rgb(244, 109, 67),
+    Color::rgb(215, 48, 39),
+];
+const SWATCH_SIZE: f32 = 14.0;
+const ROW_HEIGHT: f32 = 20.0;
+const LABEL_SIZE: u32 = 14;
+
+pub fn categorical(index: usize) -> Color {
+    CATEGORICAL[index % CATEGORICAL.len()]
+}
+
+// Spreads `count` classes over the whole palette, the first and last get its ends
+pub fn sequential(class: usize, count: usize) -> Color {
+    SEQUENTIAL[class * (SEQUENTIAL.len() - 1) / (count - 1).max(1)]
+}
+
+// Top left corner of the swatches, one row per entry above the bottom right corner
+fn origin(size: Vector2u, rows: usize) -> Vector2f {
+    Vector2f::new(
+        size.x as f32 - 150.0,
+        size.y as f32 - 20.0 - rows as f32 * ROW_HEIGHT,
+    )
+}
+
+pub fn draw(
+    render_target: &mut dyn RenderTarget,
+    entries: &[(Color, &str)],
+    size: Vector2u,
+    background: Color,
+    font: Option<&Font>,
+) {
+    let origin = origin(size, entries.len());
+    let mut swatches = Vec::new();
+    for (i, (color, _)) in entries.iter().enumerate() {
+        let top = origin.y + i as f32 * ROW_HEIGHT;
+        for (x, y) in [
+            (0.0, 0.0),
+            (1.0, 0.0),
+            (1.0, 1.0),
+            (0.0, 0.0),
+            (1.0, 1.0),
+            (0.0, 1.0),
+        ] {
+            swatches.push(Vertex::with_pos_color(
+                Vector2f::new(origin.x + x * SWATCH_SIZE, top + y * SWATCH_SIZE),
+                *color,
+            ));
+        }
+    }
+    render_target.draw_primitives(&swatches, PrimitiveType::TRIANGLES, &RenderStates::DEFAULT);
+
+    if let Some(font) = font {
+        for (i, (_, name)) in entries.iter().enumerate() {
+            let mut label = Text::new(*name, font, LABEL_SIZE);
+            label.set_fill_color(Color::WHITE);
+            label.set_outline_color(background);
+            label.set_outline_thickness(1.5);
+            label.set_position(origin + Vector2f::new(22.0, i as f32 * ROW_HEIGHT - 2.0));
+            render_target.draw(&label);
+        }
+    }
+}
+
+pub fn draw_to_svg(svg: &mut Svg, entries: &[(Color, &str)], size: Vector2u) {
+    let origin = origin(size, entries.len());
+    for (i, (color, name)) in entries.iter().enumerate() {
+        let top = origin.y + i as f32 * ROW_HEIGHT;
+        svg.rect(origin.x, top, SWATCH_SIZE, SWATCH_SIZE, *color);
+        svg.text(origin.x + 22.0, top + 12.0, LABEL_SIZE, Color::WHITE, name);
+    }
+}
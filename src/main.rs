@@ -1,5 +1,37 @@
+mod between;
+mod config;
+mod curve_file;
+mod curve_stats;
+mod data_array;
+mod expression;
+mod frame_times;
+#[cfg(test)]
+mod golden_tests;
+mod grid;
+mod intersections;
+mod keymap;
+mod legend;
+mod plugins;
+mod polar_data;
+mod radar;
+mod radial_axis;
+#[cfg(test)]
+mod raster;
+mod region;
+mod riemann;
+mod rose;
+mod sample_export;
+mod script;
+mod spider;
+mod stream;
+mod svg;
+mod tracing;
 mod viewer;
+mod wind_rose;
 
+use config::Config;
+use curve_file::CurveWatcher;
+use stream::Stream;
 use viewer::App;
 
 const N: u8 = 4;
@@ -9,19 +41,33 @@ const K: f32 = N as f32 / D as f32;
 fn main() {
     let mut app = App::new();
 
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match (arg.as_str(), args.next()) {
+            ("--font", Some(path)) => app.font_path = Some(path),
+            ("--config", Some(path)) => app.config = Config::new(&path),
+            ("--plugins", Some(path)) => app.plugin_directory = Some(path),
+            ("--curve", Some(path)) => app.curve_watcher = Some(CurveWatcher::new(&path)),
+            ("--stream", Some(source)) => match Stream::open(&source) {
+                Ok(stream) => app.stream = Some(stream),
+                Err(error) => {
+                    eprintln!("{}: {}", source, error);
+                    return;
+                }
+            },
+            (_, _) => {
+                eprintln!("Usage: polar-graphs [--config <file>] [--font <file>] [--curve <file>] [--plugins <dir>] [--stream <source>]");
+                return;
+            }
+        }
+    }
+
     app.plugin_init = |app| {
-        app.angle_limit = 180.0 * if N % 2 == D % 2 { D } else { 2 * D } as f32;
+        app.angle_limit = rose::angle_limit(N, D);
         app.desired_count = (app.angle_limit / app.angle_delta).round() as usize + 1;
     };
 
-    app.plgin_angle_to_point = |point, angle| {
-        let rad = angle.to_radians();
-        let multiplier = (rad * K).cos();
-        let (rad_sin, rad_cos) = rad.sin_cos();
-
-        point.x = rad_cos * multiplier;
-        point.y = rad_sin * multiplier;
-    };
+    app.plgin_angle_to_point = Box::new(|point, angle| rose::angle_to_point(point, angle, K));
 
     app.run();
 }
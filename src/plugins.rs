@@ -0,0 +1,153 @@
+use std::env::consts::DLL_EXTENSION;
+use std::ffi::CStr;
+use std::fs;
+use std::os::raw::c_char;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use libloading::Library;
+
+use crate::curve_file::CurveDefinition;
+
+pub const ABI_VERSION: u32 = 1;
+
+type AbiVersionFn = unsafe extern "C" fn() -> u32;
+type NameFn = unsafe extern "C" fn() -> *const c_char;
+type AngleLimitFn = unsafe extern "C" fn() -> f32;
+type AngleToPointFn = unsafe extern "C" fn(angle: f32, x: *mut f32, y: *mut f32);
+type AngleToRadiusFn = unsafe extern "C" fn(angle: f32) -> f32;
+
+pub struct Plugin {
+    pub name: String,
+    pub path: PathBuf,
+    pub angle_limit: f32,
+    angle_to_point: AngleToPointFn,
+    angle_to_radius: Option<AngleToRadiusFn>,
+    library: Rc<Library>,
+}
+
+impl Plugin {
+    pub fn load(path: &Path) -> Result<Plugin, String> {
+        // SAFETY: loading a library runs its initialisation code, plugins
+        // are trusted as much as the binary itself
+        let library = unsafe { Library::new(path) }.map_err(|error| error.to_string())?;
+
+        // SAFETY: the symbol types below are the documented plugin ABI,
+        // the version check guards against plugins built for another one
+        unsafe {
+            let abi_version = *Self::symbol::<AbiVersionFn>(&library, "polar_plugin_abi_version")?;
+            let version = abi_version();
+            if version != ABI_VERSION {
+                return Err(format!(
+                    "unsupported ABI version {} (expected {})",
+                    version, ABI_VERSION
+                ));
+            }
+
+            let name = *Self::symbol::<NameFn>(&library, "polar_plugin_name")?;
+            let angle_limit = *Self::symbol::<AngleLimitFn>(&library, "polar_plugin_angle_limit")?;
+            let angle_to_point =
+                *Self::symbol::<AngleToPointFn>(&library, "polar_plugin_angle_to_point")?;
+            let angle_to_radius =
+                Self::symbol::<AngleToRadiusFn>(&library, "polar_plugin_angle_to_radius")
+                    .ok()
+                    .map(|symbol| *symbol);
+
+            let name = name();
+            if name.is_null() {
+                return Err("polar_plugin_name returned null".to_owned());
+            }
+            let name = CStr::from_ptr(name)
+                .to_str()
+                .map_err(|_| "polar_plugin_name is not valid UTF-8".to_owned())?
+                .to_owned();
+
+            let angle_limit = angle_limit();
+            if !(angle_limit > 0.0 && angle_limit.is_finite()) {
+                return Err(format!("angle limit must be positive, got {}", angle_limit));
+            }
+
+            Ok(Plugin {
+                name,
+                path: path.to_owned(),
+                angle_limit,
+                angle_to_point,
+                angle_to_radius,
+                library: Rc::new(library),
+            })
+        }
+    }
+
+    unsafe fn symbol<'a, T>(
+        library: &'a Library,
+        name: &str,
+    ) -> Result<libloading::Symbol<'a, T>, String> {
+        library
+            .get(format!("{}\0", name).as_bytes())
+            .map_err(|_| format!("missing symbol {}", name))
+    }
+
+    pub fn curve(&self) -> CurveDefinition {
+        // The function pointers below point into the library, which must stay
+        // loaded for as long as the closure can call them, so the closure owns
+        // a reference to it even though it never uses it
+        let library = self.library.clone();
+        let angle_to_point = self.angle_to_point;
+        let angle_to_radius = self.angle_to_radius;
+
+        CurveDefinition {
+            angle_to_point: Box::new(move |point, angle| {
+                let _ = &library;
+                // SAFETY: the library is kept loaded by the closure and the
+                // pointers are valid for the duration of the call
+                match angle_to_radius {
+                    Some(angle_to_radius) => {
+                        let radius = unsafe { angle_to_radius(angle) };
+                        CurveDefinition::radius_to_point(point, angle, radius)
+                    }
+                    None => {
+                        unsafe { angle_to_point(angle, &mut point.x, &mut point.y) };
+                        point.x.hypot(point.y)
+                    }
+                }
+            }),
+            angle_limit: self.angle_limit,
+            color: None,
+            background: None,
+            errors: None,
+            script_time: None,
+            between: None,
+            intersections: None,
+        }
+    }
+}
+
+pub fn discover(directory: &str) -> Vec<Plugin> {
+    let entries = match fs::read_dir(directory) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == DLL_EXTENSION)
+        })
+        .collect();
+    paths.sort();
+
+    paths
+        .into_iter()
+        .filter_map(|path| match Plugin::load(&path) {
+            Ok(plugin) => {
+                println!("Loaded plugin \"{}\" from {}", plugin.name, path.display());
+                Some(plugin)
+            }
+            Err(error) => {
+                eprintln!("Rejected plugin {}: {}", path.display(), error);
+                None
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,278 @@
+use std::fs;
+use std::path::Path;
+
+use crate::config::Config;
+use crate::curve_file::CurveDefinition;
+
+const KEYS: [&str; 6] = ["file", "theta", "r", "units", "interpolation", "normalize"];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Interpolation {
+    Nearest,
+    Linear,
+    Cubic,
+}
+
+#[derive(Clone, Debug)]
+pub enum Column {
+    Index(usize),
+    Name(String),
+}
+
+impl Column {
+    // Numbers are 1-based column indices, anything else is a header name
+    fn parse(value: &str) -> Column {
+        match value.parse::<usize>() {
+            Ok(index) if index > 0 => Column::Index(index - 1),
+            _ => Column::Name(value.to_owned()),
+        }
+    }
+
+    fn resolve(&self, header: Option<&[&str]>) -> Result<usize, String> {
+        match self {
+            Column::Index(index) => Ok(*index),
+            Column::Name(name) => header
+                .ok_or_else(|| format!("column '{}' needs a header row", name))?
+                .iter()
+                .position(|column| column == name)
+                .ok_or_else(|| format!("no column named '{}'", name)),
+        }
+    }
+}
+
+pub struct DataOptions {
+    pub theta: Column,
+    pub r: Column,
+    pub radians: bool,
+    pub interpolation: Interpolation,
+    pub normalize: bool,
+}
+
+impl Default for DataOptions {
+    fn default() -> Self {
+        DataOptions {
+            theta: Column::Index(0),
+            r: Column::Index(1),
+            radians: false,
+            interpolation: Interpolation::Linear,
+            normalize: true,
+        }
+    }
+}
+
+impl DataOptions {
+    // Reads the [data] section of a curve file, returns the data file path too
+    pub fn parse(config: &Config) -> Result<(DataOptions, String), String> {
+        let section = config.section("data");
+        if let Some((key, _)) = section.iter().find(|(key, _)| !KEYS.contains(key)) {
+            return Err(format!("data: unknown key '{}'", key));
+        }
+
+        let mut options = DataOptions::default();
+        let mut file = None;
+        for (key, value) in section {
+            match key {
+                "file" => file = Some(value),
+                "theta" => options.theta = Column::parse(value),
+                "r" => options.r = Column::parse(value),
+                "units" => {
+                    options.radians = match value {
+                        "degrees" => false,
+                        "radians" => true,
+                        _ => return Err(format!("data: invalid units '{}'", value)),
+                    }
+                }
+                "interpolation" => {
+                    options.interpolation = match value {
+                        "nearest" => Interpolation::Nearest,
+                        "linear" => Interpolation::Linear,
+                        "cubic" => Interpolation::Cubic,
+                        _ => return Err(format!("data: invalid interpolation '{}'", value)),
+                    }
+                }
+                _ => {
+                    options.normalize = match value {
+                        "true" => true,
+                        "false" => false,
+                        _ => return Err(format!("data: invalid normalize '{}'", value)),
+                    }
+                }
+            }
+        }
+
+        // Data files are looked up next to the curve file
+        let file = file.ok_or("data: missing key 'file'")?;
+        let path = Path::new(&config.path)
+            .parent()
+            .unwrap_or(Path::new(""))
+            .join(file);
+        Ok((options, path.to_string_lossy().into_owned()))
+    }
+}
+
+// A (theta, r) series, periodic over 360 degrees and sorted by angle
+pub struct PolarData {
+    samples: Vec<(f32, f32)>,
+    interpolation: Interpolation,
+}
+
+impl PolarData {
+    pub fn load(path: &str, options: &DataOptions) -> Result<PolarData, String> {
+        let text = fs::read_to_string(path).map_err(|error| format!("{}: {}", path, error))?;
+        Self::parse(&text, options).map_err(|error| format!("{}: {}", path, error))
+    }
+
+    pub fn parse(text: &str, options: &DataOptions) -> Result<PolarData, String> {
+        let delimiter = if text.contains('\t') { '\t' } else { ',' };
+        let mut rows = text
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+            .map(|(number, line)| {
+                let fields: Vec<&str> = line.split(delimiter).map(str::trim).collect();
+                (number, fields)
+            })
+            .peekable();
+
+        // The first row is a header if it is not all numbers
+        let header = match rows.peek() {
+            Some((_, fields)) if fields.iter().any(|field| field.parse::<f32>().is_err()) => {
+                rows.next().map(|(_, fields)| fields)
+            }
+            _ => None,
+        };
+        let theta_column = options.theta.resolve(header.as_deref())?;
+        let r_column = options.r.resolve(header.as_deref())?;
+
+        let mut samples = Vec::new();
+        for (number, fields) in rows {
+            let field = |column: usize, name: &str| -> Result<f32, String> {
+                let value = fields
+                    .get(column)
+                    .ok_or_else(|| format!("line {}: missing {} column", number, name))?;
+                value
+                    .parse::<f32>()
+                    .ok()
+                    .filter(|value| value.is_finite())
+                    .ok_or_else(|| format!("line {}: invalid {} '{}'", number, name, value))
+            };
+
+            let theta = field(theta_column, "theta")?;
+            let theta = if options.radians {
+                theta.to_degrees()
+            } else {
+                theta
+            };
+            samples.push((theta.rem_euclid(360.0), field(r_column, "r")?));
+        }
+
+        samples.sort_by(|a, b| a.0.total_cmp(&b.0));
+        samples.dedup_by(|b, a| a.0 == b.0);
+        if samples.len() < 2 {
+            return Err("need at least two samples with distinct angles".to_owned());
+        }
+
+        if options.normalize {
+            let max = samples.iter().fold(0.0f32, |max, (_, r)| max.max(r.abs()));
+            if max > 0.0 {
+                samples.iter_mut().for_each(|(_, r)| *r /= max);
+            }
+        }
+
+        Ok(PolarData {
+            samples,
+            interpolation: options.interpolation,
+        })
+    }
+
+    pub fn curve(self) -> CurveDefinition {
+        CurveDefinition {
+            angle_to_point: Box::new(move |point, angle| {
+                CurveDefinition::radius_to_point(point, angle, self.radius(angle))
+            }),
+            angle_limit: 360.0,
+            color: None,
+            background: None,
+            errors: None,
+            script_time: None,
+            between: None,
+            intersections: None,
+        }
+    }
+
+    // Sample k of the periodic extension, shifted by whole turns outside 0..len
+    fn sample(&self, k: isize) -> (f32, f32) {
+        let len = self.samples.len() as isize;
+        let (angle, r) = self.samples[k.rem_euclid(len) as usize];
+        (angle + k.div_euclid(len) as f32 * 360.0, r)
+    }
+
+    pub fn radius(&self, angle: f32) -> f32 {
+        let angle = angle.rem_euclid(360.0);
+        let i = self.samples.partition_point(|(sample, _)| *sample <= angle) as isize - 1;
+
+        let (a1, r1) = self.sample(i);
+        let (a2, r2) = self.sample(i + 1);
+        let t = (angle - a1) / (a2 - a1);
+
+        match self.interpolation {
+            Interpolation::Nearest => {
+                if t < 0.5 {
+                    r1
+                } else {
+                    r2
+                }
+            }
+            Interpolation::Linear => r1 + (r2 - r1) * t,
+            Interpolation::Cubic => {
+                // Catmull-Rom through the neighbouring samples
+                let (_, r0) = self.sample(i - 1);
+                let (_, r3) = self.sample(i + 2);
+                0.5 * (2.0 * r1
+                    + (r2 - r0) * t
+                    + (2.0 * r0 - 5.0 * r1 + 4.0 * r2 - r3) * t * t
+                    + (3.0 * r1 - r0 - 3.0 * r2 + r3) * t * t * t)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn named_columns_in_radians() {
+        let options = DataOptions {
+            theta: Column::Name("phi".to_owned()),
+            r: Column::Name("gain".to_owned()),
+            radians: true,
+            normalize: false,
+            ..DataOptions::default()
+        };
+        let data =
+            PolarData::parse("# pattern\ngain\tphi\n2\t0\n4\t3.1415927\n", &options).unwrap();
+
+        assert_eq!(data.radius(0.0), 2.0);
+        assert!((data.radius(90.0) - 3.0).abs() < 1e-4);
+        assert!((data.radius(180.0) - 4.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn interpolation_wraps_around() {
+        let mut options = DataOptions::default();
+        let text = "10,1\n370,2\n";
+        assert!(PolarData::parse(text, &options).is_err());
+
+        let text = "10,1\n350,0.5\n";
+        let data = PolarData::parse(text, &options).unwrap();
+        assert!((data.radius(0.0) - 0.75).abs() < 1e-6);
+        assert!((data.radius(360.0) - 0.75).abs() < 1e-6);
+
+        options.interpolation = Interpolation::Nearest;
+        let data = PolarData::parse(text, &options).unwrap();
+        assert_eq!(data.radius(5.0), 1.0);
+        assert_eq!(data.radius(355.0), 0.5);
+    }
+}
@@ -0,0 +1,358 @@
+use std::fs;
+
+use sfml::graphics::{
+    Color, Font, PrimitiveType, RenderStates, RenderTarget, Text, Transformable, Vertex,
+};
+use sfml::system::{Vector2f, Vector2u};
+
+use crate::config::Config;
+use crate::curve_file::CurveDefinition;
+use crate::data_array;
+use crate::stream::Record;
+
+const BEAM_LINES: usize = 24;
+const BLIP_SIZE: f32 = 2.5;
+
+struct Target {
+    position: Vector2f,
+    velocity: Vector2f,
+}
+
+enum Source {
+    Synthetic {
+        targets: Vec<Target>,
+        seed: u64,
+    },
+    Recorded {
+        scans: Vec<Vec<Record>>,
+        index: usize,
+    },
+}
+
+// A plotted echo, `painted` is the total sweep (in degrees) when the beam passed it
+struct Return {
+    bearing: f32,
+    range: f32,
+    intensity: f32,
+    painted: f64,
+}
+
+pub struct Radar {
+    pub range: f32,
+    pub rings: u32,
+    pub persistence: f32,
+    pub beam_width: f32,
+    pub color: Color,
+    source: Source,
+    returns: Vec<Return>,
+    bearing: f32,
+    sweep: f64,
+}
+
+impl Radar {
+    pub fn synthetic(count: usize, seed: u64) -> Radar {
+        let mut seed = seed.max(1);
+        let targets = (0..count)
+            .map(|_| {
+                let distance = 0.15 + 0.8 * next_random(&mut seed);
+                Self::spawn(&mut seed, distance, false)
+            })
+            .collect();
+        let source = Source::Synthetic { targets, seed };
+
+        Radar {
+            range: 1.0,
+            rings: 4,
+            persistence: 0.35,
+            beam_width: 12.0,
+            color: Color::rgb(64, 255, 96),
+            source,
+            returns: Vec::new(),
+            bearing: 0.0,
+            sweep: 0.0,
+        }
+    }
+
+    // Plots as "bearing range [intensity]" lines, a line starting with "---"
+    // begins the next scan, one scan is shown per rotation
+    pub fn recorded(text: &str) -> Result<Radar, String> {
+        let mut scans = vec![Vec::new()];
+        for line in text.lines() {
+            if line.trim_start().starts_with("---") {
+                scans.push(Vec::new());
+            } else if let Some(record) = Record::parse(line) {
+                scans.last_mut().unwrap().push(record);
+            }
+        }
+        scans.retain(|scan| !scan.is_empty());
+        if scans.is_empty() {
+            return Err("no plots found".to_owned());
+        }
+
+        let max_range = scans
+            .iter()
+            .flatten()
+            .fold(0.0f32, |max, record| max.max(record.distance.abs()));
+
+        // Intensities are relative to the strongest return in the file
+        let max_intensity = scans
+            .iter()
+            .flatten()
+            .filter_map(|record| record.intensity)
+            .fold(0.0f32, f32::max);
+        if max_intensity > 0.0 {
+            for record in scans.iter_mut().flatten() {
+                record.intensity = record.intensity.map(|intensity| intensity / max_intensity);
+            }
+        }
+
+        let mut radar = Self::synthetic(0, 1);
+        radar.range = if max_range > 0.0 { max_range } else { 1.0 };
+        radar.source = Source::Recorded { scans, index: 0 };
+        Ok(radar)
+    }
+
+    pub fn from_config(config: &Config) -> Radar {
+        let mut radar = match config.get("radar", "file") {
+            Some(path) => match fs::read_to_string(path)
+                .map_err(|error| error.to_string())
+                .and_then(|text| Self::recorded(&text))
+            {
+                Ok(radar) => radar,
+                Err(error) => {
+                    eprintln!("{}: {}", path, error);
+                    Self::synthetic(12, 1)
+                }
+            },
+            None => {
+                let count = Self::parse(config, "targets", 12usize, |count| count <= 1000);
+                let seed = Self::parse(config, "seed", 1u64, |_| true);
+                Self::synthetic(count, seed)
+            }
+        };
+
+        radar.range = Self::parse(config, "range", radar.range, |range| {
+            range > 0.0 && range.is_finite()
+        });
+        radar.rings = Self::parse(config, "rings", radar.rings, |rings| rings <= 20);
+        radar.persistence = Self::parse(config, "persistence", radar.persistence, |turns| {
+            turns > 0.0 && turns.is_finite()
+        });
+        radar.beam_width = Self::parse(config, "beam_width", radar.beam_width, |width| {
+            (0.0..=360.0).contains(&width)
+        });
+        if let Some(value) = config.get("radar", "color") {
+            match CurveDefinition::color_from_text(value) {
+                Some(color) => radar.color = color,
+                None => eprintln!("{}: invalid radar color \"{}\"", config.path, value),
+            }
+        }
+        radar
+    }
+
+    fn parse<T: std::str::FromStr + Copy>(
+        config: &Config,
+        key: &str,
+        default: T,
+        valid: impl Fn(T) -> bool,
+    ) -> T {
+        match config.get("radar", key) {
+            Some(value) => match value.parse::<T>() {
+                Ok(parsed) if valid(parsed) => parsed,
+                _ => {
+                    eprintln!("{}: invalid radar {} \"{}\"", config.path, key, value);
+                    default
+                }
+            },
+            None => default,
+        }
+    }
+
+    // Inbound targets head roughly towards the center
+    fn spawn(seed: &mut u64, distance: f32, inbound: bool) -> Target {
+        let bearing = next_random(seed) * 360.0;
+        let heading = if inbound {
+            bearing + 180.0 + (next_random(seed) - 0.5) * 120.0
+        } else {
+            next_random(seed) * 360.0
+        };
+        let speed = 0.01 + 0.04 * next_random(seed);
+        Target {
+            position: polar(bearing, distance),
+            velocity: polar(heading, speed),
+        }
+    }
+
+    // Moves the targets by `dt` seconds and paints whatever the sweep passed
+    // on its way from the previous bearing to `angle`
+    pub fn update(&mut self, angle: f32, dt: f32) {
+        let bearing = angle.rem_euclid(360.0);
+        let moved = (bearing - self.bearing).rem_euclid(360.0);
+        let previous = self.bearing;
+        self.bearing = bearing;
+        self.sweep += moved as f64;
+
+        let passed = |plot: f32| (plot - previous).rem_euclid(360.0) < moved;
+        let sweep = self.sweep;
+        let returns = &mut self.returns;
+
+        match &mut self.source {
+            Source::Synthetic { targets, seed } => {
+                for target in targets.iter_mut() {
+                    target.position += target.velocity * dt;
+                    if target.position.x.hypot(target.position.y) > 1.0 {
+                        *target = Self::spawn(seed, 0.95, true);
+                    }
+
+                    let plot = target.position.x.atan2(target.position.y).to_degrees();
+                    if passed(plot) {
+                        returns.push(Return {
+                            bearing: plot,
+                            range: target.position.x.hypot(target.position.y) * self.range,
+                            intensity: 1.0,
+                            painted: sweep,
+                        });
+                    }
+                }
+            }
+            Source::Recorded { scans, index } => {
+                if bearing < previous && moved > 0.0 {
+                    *index = (*index + 1) % scans.len();
+                }
+                for record in scans[*index].iter().filter(|record| passed(record.angle)) {
+                    returns.push(Return {
+                        bearing: record.angle,
+                        range: record.distance,
+                        intensity: record.intensity.unwrap_or(1.0).max(0.0),
+                        painted: sweep,
+                    });
+                }
+            }
+        }
+
+        // Anything older than a turn has been painted over by now
+        self.returns
+            .retain(|plot| self.sweep - plot.painted < 360.0);
+    }
+
+    pub fn clear(&mut self) {
+        self.returns.clear();
+    }
+
+    pub fn draw(
+        &self,
+        render_target: &mut dyn RenderTarget,
+        size: Vector2u,
+        background: Color,
+        font: Option<&Font>,
+    ) {
+        render_target.clear(background);
+
+        let radius = data_array::get_radius(size, data_array::MARGIN);
+        let center = Vector2f::new(size.x as f32 / 2.0, size.y as f32 / 2.0);
+        let to_screen = |bearing: f32, range: f32| {
+            let point = polar(bearing, range / self.range * radius);
+            Vector2f::new(center.x + point.x, center.y - point.y)
+        };
+        let dim = Color {
+            a: 90,
+            ..self.color
+        };
+
+        for ring in 1..=self.rings {
+            let range = self.range * ring as f32 / self.rings as f32;
+            let circle: Vec<Vertex> = (0..=120)
+                .map(|i| Vertex::with_pos_color(to_screen(i as f32 * 3.0, range), dim))
+                .collect();
+            render_target.draw_primitives(
+                &circle,
+                PrimitiveType::LINE_STRIP,
+                &RenderStates::DEFAULT,
+            );
+
+            if let Some(font) = font {
+                let mut label = Text::new(&format!("{}", range), font, 12);
+                label.set_fill_color(dim);
+                label.set_position(to_screen(0.0, range) + Vector2f::new(4.0, 0.0));
+                render_target.draw(&label);
+            }
+        }
+
+        // Beam with a short fading afterglow behind it
+        let mut beam = Vec::with_capacity(BEAM_LINES * 2);
+        for i in 0..BEAM_LINES {
+            let fraction = i as f32 / BEAM_LINES as f32;
+            let color = Color {
+                a: ((1.0 - fraction).powi(2) * 255.0) as u8,
+                ..self.color
+            };
+            let bearing = self.bearing - fraction * self.beam_width;
+            beam.push(Vertex::with_pos_color(center, color));
+            beam.push(Vertex::with_pos_color(
+                to_screen(bearing, self.range),
+                color,
+            ));
+        }
+        render_target.draw_primitives(&beam, PrimitiveType::LINES, &RenderStates::DEFAULT);
+
+        let mut blips = Vec::with_capacity(self.returns.len() * 6);
+        for plot in &self.returns {
+            let age = ((self.sweep - plot.painted) / 360.0) as f32;
+            let brightness = (-age / self.persistence).exp() * plot.intensity;
+            let color = Color {
+                a: (brightness * 255.0) as u8,
+                ..self.color
+            };
+
+            let position = to_screen(plot.bearing, plot.range);
+            let corners = [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)]
+                .map(|(x, y)| position + Vector2f::new(x, y) * BLIP_SIZE);
+            for corner in [0, 1, 2, 0, 2, 3] {
+                blips.push(Vertex::with_pos_color(corners[corner], color));
+            }
+        }
+        render_target.draw_primitives(&blips, PrimitiveType::TRIANGLES, &RenderStates::DEFAULT);
+    }
+}
+
+// Radar convention: bearing 0 points up and grows clockwise
+fn polar(bearing: f32, distance: f32) -> Vector2f {
+    let (sin, cos) = bearing.to_radians().sin_cos();
+    Vector2f::new(sin * distance, cos * distance)
+}
+
+// xorshift64, good enough for placing synthetic targets
+fn next_random(seed: &mut u64) -> f32 {
+    *seed ^= *seed << 13;
+    *seed ^= *seed >> 7;
+    *seed ^= *seed << 17;
+    (*seed >> 40) as f32 / (1u64 << 24) as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sweep_paints_recorded_scans() {
+        let mut radar = Radar::recorded("# scan 1\n10 5 50\n200 10 100\n---\n90 2\n").unwrap();
+        assert_eq!(radar.range, 10.0);
+
+        radar.update(100.0, 0.0);
+        assert_eq!(radar.returns.len(), 1);
+        assert_eq!(radar.returns[0].intensity, 0.5);
+
+        radar.update(359.0, 0.0);
+        assert_eq!(radar.returns.len(), 2);
+
+        // Crossing north switches to the next scan
+        radar.update(370.0, 0.0);
+        radar.update(455.0, 0.0);
+        assert_eq!(radar.returns.len(), 3);
+        assert_eq!(radar.returns[2].range, 2.0);
+
+        // A turn later the first echoes have faded out
+        radar.update(565.0, 0.0);
+        assert_eq!(radar.returns.len(), 2);
+    }
+}
@@ -0,0 +1,433 @@
+use std::ops::Range;
+
+use sfml::graphics::{Color, Vertex};
+use sfml::system::{Vector2f, Vector2u};
+
+use crate::config::Config;
+use crate::curve_file::CurveDefinition;
+use crate::data_array;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RadialScale {
+    Linear,
+    Sqrt,
+    Log,
+    Decibel,
+}
+
+impl RadialScale {
+    pub fn name(self) -> &'static str {
+        match self {
+            RadialScale::Linear => "linear",
+            RadialScale::Sqrt => "sqrt",
+            RadialScale::Log => "log",
+            RadialScale::Decibel => "db",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<RadialScale> {
+        match name {
+            "linear" => Some(RadialScale::Linear),
+            "sqrt" => Some(RadialScale::Sqrt),
+            "log" => Some(RadialScale::Log),
+            "db" => Some(RadialScale::Decibel),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Fit {
+    None,
+    Radius,
+    Box,
+}
+
+impl Fit {
+    pub fn name(self) -> &'static str {
+        match self {
+            Fit::None => "none",
+            Fit::Radius => "radius",
+            Fit::Box => "box",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Fit> {
+        match name {
+            "none" => Some(Fit::None),
+            "radius" => Some(Fit::Radius),
+            "box" => Some(Fit::Box),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NegativeRadius {
+    Reflect,
+    Clamp,
+    Color,
+    Dash,
+}
+
+impl NegativeRadius {
+    pub fn name(self) -> &'static str {
+        match self {
+            NegativeRadius::Reflect => "reflect",
+            NegativeRadius::Clamp => "clamp",
+            NegativeRadius::Color => "color",
+            NegativeRadius::Dash => "dash",
+        }
+    }
+
+    pub fn next(self) -> NegativeRadius {
+        match self {
+            NegativeRadius::Reflect => NegativeRadius::Clamp,
+            NegativeRadius::Clamp => NegativeRadius::Color,
+            NegativeRadius::Color => NegativeRadius::Dash,
+            NegativeRadius::Dash => NegativeRadius::Reflect,
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<NegativeRadius> {
+        match name {
+            "reflect" => Some(NegativeRadius::Reflect),
+            "clamp" => Some(NegativeRadius::Clamp),
+            "color" => Some(NegativeRadius::Color),
+            "dash" => Some(NegativeRadius::Dash),
+            _ => None,
+        }
+    }
+}
+
+// Maps curve points to the unit circle the screen is built around: the
+// distance from the origin goes through the radial scale, then the fit
+// shifts and shrinks the result so the whole period stays on screen
+pub struct RadialAxis {
+    pub scale: RadialScale,
+    pub floor: f32,
+    pub fit: Fit,
+    pub margin: f32,
+    pub jump: f32,
+    pub clip: f32,
+    pub negative: NegativeRadius,
+    pub negative_color: Color,
+    pub dash: f32,
+    pub extent: f32,
+    pub offset: Vector2f,
+}
+
+impl Default for RadialAxis {
+    fn default() -> Self {
+        RadialAxis {
+            scale: RadialScale::Linear,
+            floor: -40.0,
+            fit: Fit::None,
+            margin: data_array::MARGIN,
+            jump: 1.0,
+            clip: 4.0,
+            negative: NegativeRadius::Reflect,
+            negative_color: Color::rgb(255, 96, 64),
+            dash: 4.0,
+            extent: 1.0,
+            offset: Vector2f::default(),
+        }
+    }
+}
+
+impl RadialAxis {
+    pub fn load(&mut self, config: &Config) {
+        if let Some(value) = config.get("axis", "scale") {
+            match RadialScale::from_name(value) {
+                Some(scale) => self.scale = scale,
+                None => eprintln!("{}: invalid axis scale \"{}\"", config.path, value),
+            }
+        }
+
+        if let Some(value) = config.get("axis", "floor") {
+            match value.parse::<f32>() {
+                Ok(floor) if floor < 0.0 && floor.is_finite() => self.floor = floor,
+                _ => eprintln!("{}: invalid axis floor \"{}\"", config.path, value),
+            }
+        }
+
+        if let Some(value) = config.get("axis", "fit") {
+            match Fit::from_name(value) {
+                Some(fit) => self.fit = fit,
+                None => eprintln!("{}: invalid axis fit \"{}\"", config.path, value),
+            }
+        }
+
+        if let Some(value) = config.get("axis", "margin") {
+            match value.parse::<f32>() {
+                Ok(margin) if margin >= 0.0 && margin.is_finite() => self.margin = margin,
+                _ => eprintln!("{}: invalid axis margin \"{}\"", config.path, value),
+            }
+        }
+
+        if let Some(value) = config.get("axis", "jump") {
+            match value.parse::<f32>() {
+                Ok(jump) if jump >= 0.0 && jump.is_finite() => self.jump = jump,
+                _ => eprintln!("{}: invalid axis jump \"{}\"", config.path, value),
+            }
+        }
+
+        if let Some(value) = config.get("axis", "clip") {
+            match value.parse::<f32>() {
+                Ok(clip) if clip > 0.0 && clip.is_finite() => self.clip = clip,
+                _ => eprintln!("{}: invalid axis clip \"{}\"", config.path, value),
+            }
+        }
+
+        if let Some(value) = config.get("axis", "negative") {
+            match NegativeRadius::from_name(value) {
+                Some(negative) => self.negative = negative,
+                None => eprintln!("{}: invalid axis negative \"{}\"", config.path, value),
+            }
+        }
+
+        if let Some(value) = config.get("axis", "negative_color") {
+            match CurveDefinition::color_from_text(value) {
+                Some(color) => self.negative_color = color,
+                None => eprintln!("{}: invalid axis negative_color \"{}\"", config.path, value),
+            }
+        }
+
+        if let Some(value) = config.get("axis", "dash") {
+            match value.parse::<f32>() {
+                Ok(dash) if dash > 0.0 && dash.is_finite() => self.dash = dash,
+                _ => eprintln!("{}: invalid axis dash \"{}\"", config.path, value),
+            }
+        }
+    }
+
+    // Distance from the origin on the scale, 1 stays 1 in every mode and
+    // decibels below the floor end up in the center
+    pub fn map(&self, r: f32) -> f32 {
+        match self.scale {
+            RadialScale::Linear => r,
+            RadialScale::Sqrt => r.sqrt(),
+            RadialScale::Log => r.ln_1p() / 2f32.ln(),
+            RadialScale::Decibel => ((20.0 * r.log10() - self.floor) / -self.floor).max(0.0),
+        }
+    }
+
+    pub fn unmap(&self, r: f32) -> f32 {
+        match self.scale {
+            RadialScale::Linear => r,
+            RadialScale::Sqrt => r * r,
+            RadialScale::Log => r.exp2() - 1.0,
+            RadialScale::Decibel => 10f32.powf((r * -self.floor + self.floor) / 20.0),
+        }
+    }
+
+    fn scaled(&self, point: Vector2f) -> Vector2f {
+        let r = point.x.hypot(point.y);
+        if self.scale == RadialScale::Linear || r == 0.0 {
+            point
+        } else {
+            point * (self.map(r) / r)
+        }
+    }
+
+    pub fn project(&self, point: &mut Vector2f) {
+        *point = (self.scaled(*point) - self.offset) / self.extent;
+    }
+
+    pub fn unproject(&self, point: &mut Vector2f) {
+        let scaled = *point * self.extent + self.offset;
+        let r = scaled.x.hypot(scaled.y);
+        *point = if self.scale == RadialScale::Linear || r == 0.0 {
+            scaled
+        } else {
+            scaled * (self.unmap(r) / r)
+        };
+    }
+
+    // Curves reflect points with a negative radius through the origin, the
+    // other modes move them to the origin or leave every other dash (in
+    // degrees of the angle) undefined so the line breaks there
+    fn place_negative(&self, point: &mut Vector2f, r: f32, angle: f32) {
+        if r >= 0.0 || r.is_nan() {
+            return;
+        }
+        match self.negative {
+            NegativeRadius::Clamp => *point = Vector2f::default(),
+            NegativeRadius::Dash if (angle / self.dash).rem_euclid(2.0) >= 1.0 => {
+                *point = Vector2f::new(f32::NAN, f32::NAN)
+            }
+            _ => (),
+        }
+    }
+
+    pub fn place(&self, point: &mut Vector2f, r: f32, angle: f32) {
+        self.place_negative(point, r, angle);
+        self.project(point);
+    }
+
+    // A point of a curve in screen space, placed the way the curve is
+    pub fn to_screen(&self, mut point: Vector2f, r: f32, angle: f32, size: Vector2u) -> Vector2f {
+        self.place(&mut point, r, angle);
+        data_array::unit_to_screen_point(&mut point, size, self.margin);
+        point
+    }
+
+    // Turns a curve into vertecies in unit space, keeping their alpha
+    pub fn projected<'a>(
+        &'a self,
+        angle_to_point: &'a dyn Fn(&mut Vector2f, f32) -> f32,
+        color: Color,
+    ) -> impl Fn(&mut Vertex, f32) + 'a {
+        move |vertex, angle| {
+            let r = angle_to_point(&mut vertex.position, angle);
+            self.place(&mut vertex.position, r, angle);
+
+            let color = if r < 0.0 && self.negative == NegativeRadius::Color {
+                self.negative_color
+            } else {
+                color
+            };
+            vertex.color = Color {
+                a: vertex.color.a,
+                ..color
+            };
+        }
+    }
+
+    // Jump and clip distances are in view radii, a jump of 0 never breaks
+    pub fn segments(
+        &self,
+        points: impl Iterator<Item = Vector2f>,
+        size: Vector2u,
+    ) -> Vec<Range<usize>> {
+        let radius = data_array::get_radius(size, self.margin);
+        let center = Vector2f::new(size.x as f32 / 2.0, size.y as f32 / 2.0);
+        data_array::segments(points, center, radius * self.clip, radius * self.jump)
+    }
+
+    // Measures one period of the curve to fill the screen with it
+    pub fn fit(
+        &mut self,
+        angle_to_point: &dyn Fn(&mut Vector2f, f32) -> f32,
+        angle_limit: f32,
+        angle_delta: f32,
+    ) {
+        self.extent = 1.0;
+        self.offset = Vector2f::default();
+        if self.fit == Fit::None {
+            return;
+        }
+
+        let mut min = Vector2f::new(f32::INFINITY, f32::INFINITY);
+        let mut max = Vector2f::new(f32::NEG_INFINITY, f32::NEG_INFINITY);
+        let mut radius = 0.0f32;
+        for angle in data_array::period_angles(angle_limit, angle_delta) {
+            let mut point = Vector2f::default();
+            let r = angle_to_point(&mut point, angle);
+            self.place_negative(&mut point, r, angle);
+            let point = self.scaled(point);
+            if !point.x.is_finite() || !point.y.is_finite() {
+                continue;
+            }
+
+            min = Vector2f::new(min.x.min(point.x), min.y.min(point.y));
+            max = Vector2f::new(max.x.max(point.x), max.y.max(point.y));
+            radius = radius.max(point.x.hypot(point.y));
+        }
+
+        let (offset, extent) = match self.fit {
+            Fit::Box => ((min + max) / 2.0, (max.x - min.x).max(max.y - min.y) / 2.0),
+            _ => (Vector2f::default(), radius),
+        };
+        if extent > 0.0 && extent.is_finite() {
+            self.offset = offset;
+            self.extent = extent;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limacon(point: &mut Vector2f, angle: f32) -> f32 {
+        let (sin, cos) = angle.to_radians().sin_cos();
+        let r = 1.0 + 2.0 * cos;
+        point.x = r * cos;
+        point.y = r * sin;
+        r
+    }
+
+    #[test]
+    fn scales_keep_the_unit_circle() {
+        let mut axis = RadialAxis::default();
+        for scale in [
+            RadialScale::Linear,
+            RadialScale::Sqrt,
+            RadialScale::Log,
+            RadialScale::Decibel,
+        ] {
+            axis.scale = scale;
+            assert!((axis.map(1.0) - 1.0).abs() < 1e-6, "{}", scale.name());
+            assert!((axis.unmap(axis.map(0.25)) - 0.25).abs() < 1e-5);
+        }
+
+        assert!((axis.map(0.1) - 0.5).abs() < 1e-6);
+        assert_eq!(axis.map(0.001), 0.0);
+    }
+
+    #[test]
+    fn negative_radius_modes() {
+        let mut axis = RadialAxis::default();
+        let mut vertex = Vertex::default();
+        let color = Color::rgba(10, 20, 30, 40);
+        vertex.color.a = 99;
+
+        axis.projected(&limacon, color)(&mut vertex, 180.0);
+        assert!((vertex.position.x - 1.0).abs() < 1e-6);
+        assert_eq!(vertex.color, Color::rgba(10, 20, 30, 99));
+
+        axis.negative = NegativeRadius::Clamp;
+        axis.projected(&limacon, color)(&mut vertex, 180.0);
+        assert_eq!(vertex.position, Vector2f::default());
+
+        axis.negative = NegativeRadius::Color;
+        axis.projected(&limacon, color)(&mut vertex, 180.0);
+        assert_eq!(
+            vertex.color,
+            Color {
+                a: 99,
+                ..axis.negative_color
+            }
+        );
+        axis.projected(&limacon, color)(&mut vertex, 0.0);
+        assert_eq!(vertex.color, Color::rgba(10, 20, 30, 99));
+
+        axis.negative = NegativeRadius::Dash;
+        axis.projected(&limacon, color)(&mut vertex, 178.0);
+        assert!((vertex.position.x - 1.0).abs() < 1e-2);
+        axis.projected(&limacon, color)(&mut vertex, 182.0);
+        assert!(vertex.position.x.is_nan());
+    }
+
+    #[test]
+    fn fit_brings_the_curve_on_screen() {
+        let mut axis = RadialAxis {
+            fit: Fit::Radius,
+            ..RadialAxis::default()
+        };
+        axis.fit(&limacon, 360.0, 1.0);
+        assert!((axis.extent - 3.0).abs() < 1e-4);
+
+        axis.fit = Fit::Box;
+        axis.fit(&limacon, 360.0, 1.0);
+        assert!((axis.offset.x - 1.4375).abs() < 1e-3);
+        assert!((axis.extent - 1.76).abs() < 1e-2);
+
+        axis.scale = RadialScale::Sqrt;
+        let mut point = Vector2f::new(3.0, -1.0);
+        axis.project(&mut point);
+        axis.unproject(&mut point);
+        assert!((point.x - 3.0).abs() < 1e-4 && (point.y + 1.0).abs() < 1e-4);
+    }
+}
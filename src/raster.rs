@@ -0,0 +1,131 @@
+use sfml::graphics::{Color, Vertex};
+use sfml::system::{Vector2f, Vector2u};
+
+use crate::viewer::Layers;
+
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<[u8; 4]>,
+}
+
+impl Image {
+    pub fn new(width: u32, height: u32, color: Color) -> Image {
+        Image {
+            width,
+            height,
+            pixels: vec![[color.r, color.g, color.b, color.a]; (width * height) as usize],
+        }
+    }
+
+    fn blend(&mut self, x: i64, y: i64, color: [f32; 4]) {
+        if x < 0 || y < 0 || x >= self.width as i64 || y >= self.height as i64 {
+            return;
+        }
+
+        let pixel = &mut self.pixels[(y * self.width as i64 + x) as usize];
+        let alpha = color[3] / 255.0;
+        for channel in 0..3 {
+            pixel[channel] =
+                (color[channel] * alpha + pixel[channel] as f32 * (1.0 - alpha)).round() as u8;
+        }
+        pixel[3] = (color[3] + pixel[3] as f32 * (1.0 - alpha)).round() as u8;
+    }
+}
+
+// Software counterpart of App::draw_frame: clears to the background, fills
+// the triangles in their first vertex color and draws the outlines and every
+// segment of the vertecies as one pixel wide lines without antialiasing,
+// interpolating vertex colors and alpha blending like SFML's default states
+pub fn draw_frame(
+    size: Vector2u,
+    background: Color,
+    layers: &Layers,
+    vertecies: &[Vertex],
+) -> Image {
+    let mut image = Image::new(size.x, size.y, background);
+
+    for triangle in layers.fill.chunks_exact(3) {
+        draw_triangle(&mut image, triangle);
+    }
+
+    for line in layers.outlines.chunks_exact(2) {
+        draw_line(&mut image, &line[0], &line[1], false);
+    }
+
+    for segment in &layers.segments {
+        for (i, line) in vertecies[segment.clone()].windows(2).enumerate() {
+            draw_line(&mut image, &line[0], &line[1], i > 0);
+        }
+    }
+
+    image
+}
+
+fn draw_line(image: &mut Image, from: &Vertex, to: &Vertex, skip_first: bool) {
+    let dx = to.position.x - from.position.x;
+    let dy = to.position.y - from.position.y;
+    let steps = dx.abs().max(dy.abs()).ceil().max(1.0) as u32;
+
+    let from_color = to_rgba(from.color);
+    let to_color = to_rgba(to.color);
+
+    for step in skip_first as u32..=steps {
+        let t = step as f32 / steps as f32;
+        let mut color = [0.0; 4];
+        for channel in 0..4 {
+            color[channel] = from_color[channel] + (to_color[channel] - from_color[channel]) * t;
+        }
+
+        image.blend(
+            (from.position.x + dx * t).floor() as i64,
+            (from.position.y + dy * t).floor() as i64,
+            color,
+        );
+    }
+}
+
+// Covers pixels whose center is inside, centers on an edge belong to the
+// triangle only for its top and left edges so shared edges are drawn once
+fn draw_triangle(image: &mut Image, triangle: &[Vertex]) {
+    let cross = |a: Vector2f, b: Vector2f| a.x * b.y - a.y * b.x;
+    let [mut a, mut b, c] = [0, 1, 2].map(|i| triangle[i].position);
+    let area = cross(b - a, c - a);
+    if area == 0.0 || !area.is_finite() {
+        return;
+    }
+    if area < 0.0 {
+        std::mem::swap(&mut a, &mut b);
+    }
+
+    let edges = [(a, b), (b, c), (c, a)];
+    let inside = |point: Vector2f| {
+        edges.iter().all(|&(from, to)| {
+            let edge = to - from;
+            let side = cross(edge, point - from);
+            side > 0.0 || (side == 0.0 && (edge.y < 0.0 || (edge.y == 0.0 && edge.x > 0.0)))
+        })
+    };
+
+    let color = to_rgba(triangle[0].color);
+    let left = a.x.min(b.x).min(c.x).floor() as i64;
+    let right = a.x.max(b.x).max(c.x).ceil() as i64;
+    let top = a.y.min(b.y).min(c.y).floor() as i64;
+    let bottom = a.y.max(b.y).max(c.y).ceil() as i64;
+    for y in top..bottom {
+        for x in left..right {
+            if inside(Vector2f::new(x as f32 + 0.5, y as f32 + 0.5)) {
+                image.blend(x, y, color);
+            }
+        }
+    }
+}
+
+fn to_rgba(color: Color) -> [f32; 4] {
+    [
+        color.r as f32,
+        color.g as f32,
+        color.b as f32,
+        color.a as f32,
+    ]
+}
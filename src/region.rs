@@ -0,0 +1,239 @@
+use sfml::graphics::{Color, Vertex};
+use sfml::system::Vector2f;
+
+use crate::config::Config;
+use crate::curve_file::CurveDefinition;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FillRule {
+    NonZero,
+    EvenOdd,
+}
+
+impl FillRule {
+    pub fn name(self) -> &'static str {
+        match self {
+            FillRule::NonZero => "nonzero",
+            FillRule::EvenOdd => "evenodd",
+        }
+    }
+
+    pub fn next(self) -> FillRule {
+        match self {
+            FillRule::NonZero => FillRule::EvenOdd,
+            FillRule::EvenOdd => FillRule::NonZero,
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<FillRule> {
+        match name {
+            "nonzero" => Some(FillRule::NonZero),
+            "evenodd" => Some(FillRule::EvenOdd),
+            _ => None,
+        }
+    }
+}
+
+// How the region enclosed by the curve is filled, the outline is the curve itself
+#[derive(Clone, PartialEq)]
+pub struct Fill {
+    pub rule: FillRule,
+    pub color: Color,
+    pub outline: bool,
+}
+
+impl Default for Fill {
+    fn default() -> Self {
+        Fill {
+            rule: FillRule::NonZero,
+            color: Color::rgba(80, 140, 255, 128),
+            outline: true,
+        }
+    }
+}
+
+impl Fill {
+    pub fn load(&mut self, config: &Config) {
+        if let Some(value) = config.get("fill", "rule") {
+            match FillRule::from_name(value) {
+                Some(rule) => self.rule = rule,
+                None => eprintln!("{}: invalid fill rule \"{}\"", config.path, value),
+            }
+        }
+
+        if let Some(value) = config.get("fill", "color") {
+            // Without an alpha component the opacity stays as it is
+            let alpha = self.color.a;
+            match CurveDefinition::color_from_text(value) {
+                Some(color) => {
+                    self.color = color;
+                    if value.split(',').count() == 3 {
+                        self.color.a = alpha;
+                    }
+                }
+                None => eprintln!("{}: invalid fill color \"{}\"", config.path, value),
+            }
+        }
+
+        if let Some(value) = config.get("fill", "opacity") {
+            match value.parse::<f32>() {
+                Ok(opacity) if (0.0..=1.0).contains(&opacity) => {
+                    self.color.a = (opacity * 255.0).round() as u8
+                }
+                _ => eprintln!("{}: invalid fill opacity \"{}\"", config.path, value),
+            }
+        }
+
+        if let Some(value) = config.get("fill", "outline") {
+            match value {
+                "true" => self.outline = true,
+                "false" => self.outline = false,
+                _ => eprintln!("{}: invalid fill outline \"{}\"", config.path, value),
+            }
+        }
+    }
+
+    // Tessellates the polygon into one pixel high strips along every
+    // scanline, which handles self-intersecting curves under either rule
+    pub fn triangles(&self, polygon: &[Vector2f]) -> Vec<Vertex> {
+        let (top, bottom) = vertical_range(polygon);
+        let mut triangles = Vec::new();
+        if !(top.is_finite() && bottom.is_finite()) {
+            return triangles;
+        }
+
+        for row in top.floor() as i64..bottom.ceil() as i64 {
+            let y = row as f32;
+            for (left, right) in spans(polygon, y + 0.5, self.rule) {
+                let corners = [
+                    Vector2f::new(left, y),
+                    Vector2f::new(right, y),
+                    Vector2f::new(right, y + 1.0),
+                    Vector2f::new(left, y + 1.0),
+                ];
+                for i in [0, 1, 2, 0, 2, 3] {
+                    triangles.push(Vertex::with_pos_color(corners[i], self.color));
+                }
+            }
+        }
+        triangles
+    }
+}
+
+// The triangles of the last polygon, made again only when the polygon (the
+// drawn points on screen) or the fill changes
+#[derive(Default)]
+pub struct FillCache {
+    key: Option<(Fill, Vec<Vector2f>)>,
+    triangles: Vec<Vertex>,
+}
+
+impl FillCache {
+    pub fn triangles(&mut self, fill: &Fill, polygon: Vec<Vector2f>) -> Vec<Vertex> {
+        let key = Some((fill.clone(), polygon));
+        if self.key != key {
+            self.key = key;
+            self.triangles = fill.triangles(&self.key.as_ref().unwrap().1);
+        }
+        self.triangles.clone()
+    }
+}
+
+fn vertical_range(polygon: &[Vector2f]) -> (f32, f32) {
+    polygon.iter().fold(
+        (f32::INFINITY, f32::NEG_INFINITY),
+        |(top, bottom), point| (top.min(point.y), bottom.max(point.y)),
+    )
+}
+
+// Parts of the horizontal line at `y` that are inside the closed polygon,
+// the last point connects back to the first
+pub fn spans(polygon: &[Vector2f], y: f32, rule: FillRule) -> Vec<(f32, f32)> {
+    let mut crossings: Vec<(f32, i32)> = Vec::new();
+    for (i, from) in polygon.iter().enumerate() {
+        let to = polygon[(i + 1) % polygon.len()];
+        if (from.y <= y) != (to.y <= y) {
+            let t = (y - from.y) / (to.y - from.y);
+            let direction = if to.y > from.y { 1 } else { -1 };
+            crossings.push((from.x + (to.x - from.x) * t, direction));
+        }
+    }
+    crossings.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut spans = Vec::new();
+    let mut winding = 0;
+    for pair in crossings.windows(2) {
+        winding += pair[0].1;
+        let inside = match rule {
+            FillRule::NonZero => winding != 0,
+            FillRule::EvenOdd => winding % 2 != 0,
+        };
+        if inside && pair[1].0 > pair[0].0 {
+            spans.push((pair[0].0, pair[1].0));
+        }
+    }
+    spans
+}
+
+// Area inside the polygon, summed over `rows` scanlines, so overlapping
+// loops count once (or not at all for even-odd) instead of once per turn
+pub fn area(polygon: &[Vector2f], rule: FillRule, rows: usize) -> f32 {
+    let (top, bottom) = vertical_range(polygon);
+    if polygon.len() < 3 || bottom <= top {
+        return 0.0;
+    }
+
+    let height = (bottom - top) / rows as f32;
+    (0..rows)
+        .map(|row| {
+            let y = top + (row as f32 + 0.5) * height;
+            spans(polygon, y, rule)
+                .iter()
+                .map(|(from, to)| (to - from) as f64)
+                .sum::<f64>()
+        })
+        .sum::<f64>() as f32
+        * height
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rules_differ_where_loops_overlap() {
+        // A square traced twice winds twice around its inside
+        let square = [(0.0, 0.0), (4.0, 0.0), (4.0, 4.0), (0.0, 4.0)].map(Vector2f::from);
+        let twice = [square, square].concat();
+        assert_eq!(spans(&twice, 2.0, FillRule::NonZero), [(0.0, 4.0)]);
+        assert_eq!(spans(&twice, 2.0, FillRule::EvenOdd), []);
+        assert_eq!(area(&square, FillRule::EvenOdd, 100), 16.0);
+
+        let fill = Fill::default();
+        assert_eq!(fill.triangles(&square).len(), 4 * 6);
+        assert!(fill.triangles(&[]).is_empty());
+    }
+
+    #[test]
+    fn cache_follows_polygon_and_rule() {
+        let square = [(0.0, 0.0), (4.0, 0.0), (4.0, 4.0), (0.0, 4.0)].map(Vector2f::from);
+        let twice = [square, square].concat();
+        let mut fill = Fill::default();
+        let mut cache = FillCache::default();
+        assert_eq!(cache.triangles(&fill, square.to_vec()).len(), 4 * 6);
+        assert_eq!(cache.triangles(&fill, twice.clone()).len(), 4 * 6);
+        fill.rule = FillRule::EvenOdd;
+        assert!(cache.triangles(&fill, twice).is_empty());
+    }
+
+    #[test]
+    fn color_keeps_opacity_without_alpha() {
+        let mut fill = Fill::default();
+        fill.load(&Config::from_text("a.ini", "[fill]\ncolor = 1, 2, 3\n"));
+        assert_eq!(fill.color, Color::rgba(1, 2, 3, 128));
+        fill.load(&Config::from_text("a.ini", "[fill]\ncolor = 4, 5, 6, 7\n"));
+        assert_eq!(fill.color, Color::rgba(4, 5, 6, 7));
+        fill.load(&Config::from_text("a.ini", "[fill]\ncolor = 4, 5, 256\n"));
+        assert_eq!(fill.color, Color::rgba(4, 5, 6, 7));
+    }
+}
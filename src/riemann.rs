@@ -0,0 +1,277 @@
+use std::iter;
+
+use sfml::graphics::{Color, Vertex};
+use sfml::system::{Vector2f, Vector2u};
+
+use crate::curve_file::CurveDefinition;
+use crate::curve_stats;
+use crate::radial_axis::RadialAxis;
+use crate::region::FillRule;
+use crate::svg::Svg;
+
+pub const MAX_SECTORS: usize = 10000;
+// Seconds before the animation doubles the number of sectors
+const ANIMATION_STEP: f32 = 0.6;
+// Intervals of Simpson's rule for the exact area per turn
+const INTERVALS_PER_TURN: f32 = 3600.0;
+
+const SECTOR_COLORS: [Color; 2] = [Color::rgba(90, 200, 120, 90), Color::rgba(60, 150, 220, 90)];
+const EDGE_COLOR: Color = Color::rgba(255, 255, 255, 70);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SampleRule {
+    Left,
+    Right,
+    Mid,
+}
+
+impl SampleRule {
+    pub fn name(self) -> &'static str {
+        match self {
+            SampleRule::Left => "left",
+            SampleRule::Right => "right",
+            SampleRule::Mid => "mid",
+        }
+    }
+
+    pub fn next(self) -> SampleRule {
+        match self {
+            SampleRule::Left => SampleRule::Right,
+            SampleRule::Right => SampleRule::Mid,
+            SampleRule::Mid => SampleRule::Left,
+        }
+    }
+
+    // Where the radius of a sector is taken, as a fraction of its width
+    fn offset(self) -> f32 {
+        match self {
+            SampleRule::Left => 0.0,
+            SampleRule::Right => 1.0,
+            SampleRule::Mid => 0.5,
+        }
+    }
+}
+
+// Approximates 1/2 * integral of r^2 over one period with `n` circular
+// sectors, which doubles from one sector until it reaches `target`
+pub struct RiemannSum {
+    pub n: usize,
+    pub target: usize,
+    pub rule: SampleRule,
+    pub exact: Option<f32>,
+    elapsed: f32,
+}
+
+impl Default for RiemannSum {
+    fn default() -> Self {
+        RiemannSum {
+            n: 1,
+            target: 64,
+            rule: SampleRule::Mid,
+            exact: None,
+            elapsed: 0.0,
+        }
+    }
+}
+
+impl RiemannSum {
+    pub fn update(&mut self, dt: f32) {
+        self.elapsed += dt;
+        while self.elapsed >= ANIMATION_STEP {
+            self.elapsed -= ANIMATION_STEP;
+            self.n = (self.n * 2).min(self.target);
+        }
+    }
+
+    // Starts the animation over towards the new number of sectors
+    pub fn set_target(&mut self, target: usize) {
+        self.target = target.clamp(1, MAX_SECTORS);
+        self.n = 1;
+        self.elapsed = 0.0;
+    }
+
+    // Start angle and radius of every sector, sectors are `width` degrees wide
+    fn samples(
+        &self,
+        angle_to_point: &dyn Fn(&mut Vector2f, f32) -> f32,
+        angle_limit: f32,
+    ) -> Vec<(f32, f32)> {
+        let width = angle_limit / self.n as f32;
+        let mut point = Vector2f::default();
+        (0..self.n)
+            .map(|k| {
+                let start = k as f32 * width;
+                let r = angle_to_point(&mut point, start + self.rule.offset() * width);
+                (start, r)
+            })
+            .collect()
+    }
+
+    pub fn approximation(
+        &self,
+        angle_to_point: &dyn Fn(&mut Vector2f, f32) -> f32,
+        angle_limit: f32,
+    ) -> f32 {
+        let width = (angle_limit / self.n as f32).to_radians();
+        self.samples(angle_to_point, angle_limit)
+            .iter()
+            .map(|(_, r)| r * r * width / 2.0)
+            .filter(|area| area.is_finite())
+            .sum()
+    }
+
+    // Simpson's rule for the area the sectors approach, kept until the curve changes
+    pub fn exact(
+        &mut self,
+        angle_to_point: &dyn Fn(&mut Vector2f, f32) -> f32,
+        angle_limit: f32,
+    ) -> f32 {
+        *self.exact.get_or_insert_with(|| {
+            let turns = angle_limit / 360.0;
+            let intervals = ((turns * INTERVALS_PER_TURN / 2.0).ceil() as usize * 2).max(2);
+            let mut point = Vector2f::default();
+            let integral = curve_stats::simpson(
+                |angle| {
+                    let r = angle_to_point(&mut point, angle);
+                    if r.is_finite() {
+                        (r * r) as f64
+                    } else {
+                        0.0
+                    }
+                },
+                0.0,
+                angle_limit,
+                intervals,
+            );
+            (integral * 1f64.to_radians() / 2.0) as f32
+        })
+    }
+
+    // Every sector as screen points: the origin followed by its arc
+    fn sectors(
+        &self,
+        angle_to_point: &dyn Fn(&mut Vector2f, f32) -> f32,
+        angle_limit: f32,
+        axis: &RadialAxis,
+        size: Vector2u,
+    ) -> Vec<Vec<Vector2f>> {
+        let width = angle_limit / self.n as f32;
+        let arc_points = (width.abs().ceil() as usize).clamp(1, 90);
+        let to_screen = |angle: f32, r: f32| {
+            let mut point = Vector2f::default();
+            CurveDefinition::radius_to_point(&mut point, angle, r);
+            axis.to_screen(point, r, angle, size)
+        };
+
+        self.samples(angle_to_point, angle_limit)
+            .into_iter()
+            .filter(|(_, r)| r.is_finite())
+            .map(|(start, r)| {
+                let arc = (0..=arc_points)
+                    .map(|i| to_screen(start + width * i as f32 / arc_points as f32, r));
+                iter::once(to_screen(0.0, 0.0)).chain(arc).collect()
+            })
+            .collect()
+    }
+
+    // Sector triangles in alternating colors and their edges as pairs of line ends
+    pub fn vertecies(
+        &self,
+        angle_to_point: &dyn Fn(&mut Vector2f, f32) -> f32,
+        angle_limit: f32,
+        axis: &RadialAxis,
+        size: Vector2u,
+    ) -> (Vec<Vertex>, Vec<Vertex>) {
+        let mut triangles = Vec::new();
+        let mut lines = Vec::new();
+        for (k, sector) in self
+            .sectors(angle_to_point, angle_limit, axis, size)
+            .iter()
+            .enumerate()
+        {
+            let color = SECTOR_COLORS[k % 2];
+            for pair in sector[1..].windows(2) {
+                for point in [sector[0], pair[0], pair[1]] {
+                    triangles.push(Vertex::with_pos_color(point, color));
+                }
+            }
+            let last = sector.len() - 1;
+            let edges = [(sector[0], sector[1]), (sector[0], sector[last])];
+            let arc = sector[1..].windows(2).map(|pair| (pair[0], pair[1]));
+            for (from, to) in edges.into_iter().chain(arc) {
+                lines.push(Vertex::with_pos_color(from, EDGE_COLOR));
+                lines.push(Vertex::with_pos_color(to, EDGE_COLOR));
+            }
+        }
+        (triangles, lines)
+    }
+
+    pub fn draw_to_svg(
+        &self,
+        svg: &mut Svg,
+        angle_to_point: &dyn Fn(&mut Vector2f, f32) -> f32,
+        angle_limit: f32,
+        axis: &RadialAxis,
+        size: Vector2u,
+    ) {
+        for (k, sector) in self
+            .sectors(angle_to_point, angle_limit, axis, size)
+            .iter()
+            .enumerate()
+        {
+            svg.path(sector, SECTOR_COLORS[k % 2], FillRule::NonZero);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::PI;
+
+    use super::*;
+
+    fn spiral(point: &mut Vector2f, angle: f32) -> f32 {
+        CurveDefinition::radius_to_point(point, angle, 1.0 + angle / 180.0)
+    }
+
+    #[test]
+    fn sums_approach_the_area() {
+        // 1/2 * integral of (1 + t/pi)^2 over half a turn is 7/6 pi
+        let exact = 7.0 / 6.0 * PI;
+        let mut sum = RiemannSum::default();
+        assert!((sum.exact(&spiral, 180.0) - exact).abs() < 1e-4);
+
+        sum.rule = SampleRule::Left;
+        assert!((sum.approximation(&spiral, 180.0) - PI / 2.0).abs() < 1e-4);
+
+        for rule in [SampleRule::Left, SampleRule::Right, SampleRule::Mid] {
+            sum.rule = rule;
+            let errors: Vec<f32> = [4, 16, 64]
+                .iter()
+                .map(|&n| {
+                    sum.n = n;
+                    (sum.approximation(&spiral, 180.0) - exact).abs()
+                })
+                .collect();
+            assert!(
+                errors.windows(2).all(|pair| pair[1] < pair[0]),
+                "{:?}",
+                errors
+            );
+        }
+        assert!((sum.approximation(&spiral, 180.0) - exact).abs() < 1e-3);
+    }
+
+    #[test]
+    fn animation_doubles_up_to_the_target() {
+        let mut sum = RiemannSum::default();
+        sum.set_target(5);
+        let steps: Vec<usize> = (0..4)
+            .map(|_| {
+                sum.update(ANIMATION_STEP);
+                sum.n
+            })
+            .collect();
+        assert_eq!(steps, [2, 4, 5, 5]);
+    }
+}
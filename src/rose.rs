@@ -0,0 +1,26 @@
+use sfml::system::Vector2f;
+
+pub fn gcd(a: u8, b: u8) -> u8 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+pub fn angle_limit(n: u8, d: u8) -> f32 {
+    let divisor = gcd(n, d).max(1);
+    let (n, d) = (n / divisor, d / divisor);
+    180.0 * if n % 2 == d % 2 { d } else { 2 * d } as f32
+}
+
+// Returns the signed radius, the point is reflected through the origin when it is negative
+pub fn angle_to_point(point: &mut Vector2f, angle: f32, k: f32) -> f32 {
+    let rad = angle.to_radians();
+    let multiplier = (rad * k).cos();
+    let (rad_sin, rad_cos) = rad.sin_cos();
+
+    point.x = rad_cos * multiplier;
+    point.y = rad_sin * multiplier;
+    multiplier
+}
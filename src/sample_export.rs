@@ -0,0 +1,276 @@
+use std::fmt::Write;
+
+use sfml::system::{Vector2f, Vector2u};
+
+use crate::config::Config;
+use crate::data_array;
+use crate::radial_axis::RadialAxis;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+impl ExportFormat {
+    pub fn name(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<ExportFormat> {
+        match name {
+            "csv" => Some(ExportFormat::Csv),
+            "json" => Some(ExportFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+pub struct Sample {
+    pub angle: f32,
+    pub r: f32,
+    pub unit: Vector2f,
+    pub screen: Vector2f,
+    pub segment: Option<usize>,
+}
+
+pub struct SampleExport {
+    pub formats: Vec<ExportFormat>,
+    pub precision: usize,
+    pub downsample: usize,
+}
+
+impl Default for SampleExport {
+    fn default() -> Self {
+        SampleExport {
+            formats: vec![ExportFormat::Csv, ExportFormat::Json],
+            precision: 6,
+            downsample: 1,
+        }
+    }
+}
+
+impl SampleExport {
+    pub fn load(&mut self, config: &Config) {
+        if let Some(value) = config.get("export", "format") {
+            let formats: Option<Vec<_>> = value
+                .split(',')
+                .map(|name| ExportFormat::from_name(name.trim()))
+                .collect();
+            match formats {
+                Some(formats) if !formats.is_empty() => self.formats = formats,
+                _ => eprintln!("{}: invalid export format \"{}\"", config.path, value),
+            }
+        }
+
+        if let Some(value) = config.get("export", "precision") {
+            match value.parse::<usize>() {
+                Ok(precision) if precision <= 9 => self.precision = precision,
+                _ => eprintln!("{}: invalid export precision \"{}\"", config.path, value),
+            }
+        }
+
+        if let Some(value) = config.get("export", "downsample") {
+            match value.parse::<usize>() {
+                Ok(downsample) if downsample > 0 => self.downsample = downsample,
+                _ => eprintln!("{}: invalid export downsample \"{}\"", config.path, value),
+            }
+        }
+    }
+
+    // Keeps every n-th sample and always the last one, so the period stays closed
+    pub fn downsampled<'a>(&self, samples: &'a [Sample]) -> Vec<&'a Sample> {
+        let mut kept: Vec<&Sample> = samples.iter().step_by(self.downsample).collect();
+        if let Some(last) = samples.last() {
+            if !std::ptr::eq(*kept.last().unwrap(), last) {
+                kept.push(last);
+            }
+        }
+        kept
+    }
+
+    pub fn to_csv(&self, samples: &[&Sample]) -> String {
+        let mut text = "angle,r,unit_x,unit_y,screen_x,screen_y,segment\n".to_owned();
+        for sample in samples {
+            for value in sample_values(sample) {
+                write!(text, "{:.*},", self.precision, value).unwrap();
+            }
+            match sample.segment {
+                Some(segment) => writeln!(text, "{}", segment).unwrap(),
+                None => writeln!(text).unwrap(),
+            }
+        }
+        text
+    }
+
+    pub fn to_json(
+        &self,
+        samples: &[&Sample],
+        curve_name: &str,
+        angle_limit: f32,
+        angle_delta: f32,
+        size: Vector2u,
+    ) -> String {
+        let mut text = String::new();
+        writeln!(text, "{{").unwrap();
+        writeln!(text, "  \"curve\": {},", json_string(curve_name)).unwrap();
+        writeln!(text, "  \"angle_limit\": {},", json_number(angle_limit, 6)).unwrap();
+        writeln!(text, "  \"angle_delta\": {},", json_number(angle_delta, 6)).unwrap();
+        writeln!(text, "  \"size\": [{}, {}],", size.x, size.y).unwrap();
+        writeln!(text, "  \"samples\": [").unwrap();
+        for (i, sample) in samples.iter().enumerate() {
+            let [angle, r, unit_x, unit_y, screen_x, screen_y] =
+                sample_values(sample).map(|value| json_number(value, self.precision));
+            writeln!(
+                text,
+                "    {{\"angle\": {}, \"r\": {}, \"unit_x\": {}, \"unit_y\": {}, \"screen_x\": {}, \"screen_y\": {}, \"segment\": {}}}{}",
+                angle,
+                r,
+                unit_x,
+                unit_y,
+                screen_x,
+                screen_y,
+                sample
+                    .segment
+                    .map_or("null".to_owned(), |segment| segment.to_string()),
+                if i + 1 < samples.len() { "," } else { "" }
+            )
+            .unwrap();
+        }
+        writeln!(text, "  ]").unwrap();
+        writeln!(text, "}}").unwrap();
+        text
+    }
+}
+
+// Evaluates the curve at the same angles as a full cycle of the F2 export,
+// samples of one connected line share a segment number, points that are
+// not drawn have none
+pub fn sample_period(
+    angle_to_point: &dyn Fn(&mut Vector2f, f32) -> f32,
+    axis: &RadialAxis,
+    angle_limit: f32,
+    angle_delta: f32,
+    size: Vector2u,
+) -> Vec<Sample> {
+    let mut samples: Vec<Sample> = data_array::period_angles(angle_limit, angle_delta)
+        .map(|angle| {
+            let mut unit = Vector2f::default();
+            let r = angle_to_point(&mut unit, angle);
+            let mut screen = unit;
+            axis.place(&mut screen, r, angle);
+            data_array::unit_to_screen_point(&mut screen, size, axis.margin);
+
+            Sample {
+                angle,
+                r,
+                unit,
+                screen,
+                segment: None,
+            }
+        })
+        .collect();
+
+    let segments = axis.segments(samples.iter().map(|sample| sample.screen), size);
+    for (segment, range) in segments.into_iter().enumerate() {
+        for sample in &mut samples[range] {
+            sample.segment = Some(segment);
+        }
+    }
+    samples
+}
+
+fn sample_values(sample: &Sample) -> [f32; 6] {
+    [
+        sample.angle,
+        sample.r,
+        sample.unit.x,
+        sample.unit.y,
+        sample.screen.x,
+        sample.screen.y,
+    ]
+}
+
+// JSON has no NaN or infinity, those become null
+pub fn json_number(value: f32, precision: usize) -> String {
+    if value.is_finite() {
+        format!("{:.*}", precision, value)
+    } else {
+        "null".to_owned()
+    }
+}
+
+pub fn json_string(text: &str) -> String {
+    let mut escaped = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if (c as u32) < 0x20 => write!(escaped, "\\u{:04x}", c as u32).unwrap(),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn circle(point: &mut Vector2f, angle: f32) -> f32 {
+        let (sin, cos) = angle.to_radians().sin_cos();
+        point.x = cos;
+        point.y = sin;
+        1.0
+    }
+
+    #[test]
+    fn period_is_closed_after_downsampling() {
+        let samples = sample_period(
+            &circle,
+            &RadialAxis::default(),
+            360.0,
+            1.0,
+            (1024, 1024).into(),
+        );
+        assert_eq!(samples.len(), 361);
+
+        let export = SampleExport {
+            downsample: 7,
+            ..SampleExport::default()
+        };
+        let kept = export.downsampled(&samples);
+        assert_eq!(kept.len(), 53);
+        assert_eq!(kept[0].angle, 0.0);
+        assert_eq!(kept.last().unwrap().angle, samples.last().unwrap().angle);
+    }
+
+    #[test]
+    fn formats_respect_precision() {
+        let sample = Sample {
+            angle: 90.0,
+            r: f32::NAN,
+            unit: Vector2f::new(0.0, 1.0),
+            screen: Vector2f::new(512.0, 50.0),
+            segment: None,
+        };
+        let export = SampleExport {
+            precision: 2,
+            ..SampleExport::default()
+        };
+
+        assert_eq!(
+            export.to_csv(&[&sample]),
+            "angle,r,unit_x,unit_y,screen_x,screen_y,segment\n90.00,NaN,0.00,1.00,512.00,50.00,\n"
+        );
+        assert!(export
+            .to_json(&[&sample], "a \"b\"", 360.0, 1.0, (1024, 1024).into())
+            .contains(
+                "\"curve\": \"a \\\"b\\\"\",\n  \"angle_limit\": 360.000000,\n  \"angle_delta\": 1.000000,\n  \"size\": [1024, 1024],\n  \"samples\": [\n    {\"angle\": 90.00, \"r\": null, \"unit_x\": 0.00, \"unit_y\": 1.00, \"screen_x\": 512.00, \"screen_y\": 50.00, \"segment\": null}\n  ]"
+            ));
+    }
+}
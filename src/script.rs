@@ -0,0 +1,183 @@
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use rhai::{Array, CallFnOptions, Dynamic, Engine, EvalAltResult, Map, Scope, AST, FLOAT};
+use sfml::graphics::Color;
+use sfml::system::Vector2f;
+
+use crate::curve_file::{CurveDefinition, ErrorSlot, TimeSlot};
+
+const MAX_OPERATIONS: u64 = 100_000;
+// Script time allowed per frame, the viewer resets it at the start of every
+// frame, so thousands of calls for a reset or an export cannot stall it
+const TIME_BUDGET: Duration = Duration::from_secs(2);
+// Operations between two looks at the clock
+const CLOCK_INTERVAL: u64 = 1024;
+
+pub fn load(text: &str) -> Result<CurveDefinition, String> {
+    let mut engine = Engine::new();
+    engine.set_max_operations(MAX_OPERATIONS);
+
+    let script_time: TimeSlot = Rc::new(Cell::new(Duration::ZERO));
+    let call_start = Rc::new(Cell::new(Instant::now()));
+    {
+        let (script_time, call_start) = (script_time.clone(), call_start.clone());
+        engine.on_progress(move |operations| {
+            let over = operations % CLOCK_INTERVAL == 0
+                && script_time.get() + call_start.get().elapsed() > TIME_BUDGET;
+            over.then_some(Dynamic::UNIT)
+        });
+    }
+
+    let ast = engine.compile(text).map_err(|error| error.to_string())?;
+    if !has_function(&ast, "point", 1) {
+        return Err("missing function point(angle)".to_owned());
+    }
+
+    let this = if has_function(&ast, "init", 0) {
+        engine
+            .call_fn::<Dynamic>(&mut Scope::new(), &ast, "init", ())
+            .map_err(|error| format!("init(): {}", error))?
+    } else {
+        Dynamic::from_map(Map::new())
+    };
+    if !this.is_map() {
+        return Err(format!(
+            "init() must return an object map, got {}",
+            this.type_name()
+        ));
+    }
+
+    let (angle_limit, color, background) = {
+        let settings = this.read_lock::<Map>().unwrap();
+        let angle_limit = match settings.get("angle_limit") {
+            Some(value) => to_float(value).ok_or("angle_limit must be a number")?,
+            None => 360.0,
+        };
+        let color =
+            to_color(settings.get("color")).ok_or("color must be [r, g, b] or [r, g, b, a]")?;
+        let background = to_color(settings.get("background"))
+            .ok_or("background must be [r, g, b] or [r, g, b, a]")?;
+        (angle_limit, color, background)
+    };
+    if !(angle_limit > 0.0 && angle_limit.is_finite()) {
+        return Err(format!("angle_limit must be positive, got {}", angle_limit));
+    }
+
+    let errors: ErrorSlot = Rc::new(RefCell::new(None));
+    let error_slot = errors.clone();
+    let time_slot = script_time.clone();
+    let failed = Cell::new(false);
+    let this = RefCell::new(this);
+
+    Ok(CurveDefinition {
+        angle_to_point: Box::new(move |point, angle| {
+            point.x = 0.0;
+            point.y = 0.0;
+            if failed.get() {
+                return 0.0;
+            }
+
+            let mut this = this.borrow_mut();
+            call_start.set(Instant::now());
+            let result = engine
+                .call_fn_with_options::<Dynamic>(
+                    CallFnOptions::new()
+                        .eval_ast(false)
+                        .bind_this_ptr(&mut this),
+                    &mut Scope::new(),
+                    &ast,
+                    "point",
+                    (angle as FLOAT,),
+                )
+                .map_err(|error| match *error {
+                    EvalAltResult::ErrorTerminated(..) => {
+                        format!("script took over {} s in one frame", TIME_BUDGET.as_secs())
+                    }
+                    error => error.to_string(),
+                })
+                .and_then(|value| to_point(point, angle, value));
+            time_slot.set(time_slot.get() + call_start.get().elapsed());
+
+            result.unwrap_or_else(|error| {
+                failed.set(true);
+                *error_slot.borrow_mut() = Some(format!("point({}): {}", angle, error));
+                0.0
+            })
+        }),
+        angle_limit,
+        color,
+        background,
+        errors: Some(errors),
+        script_time: Some(script_time),
+        between: None,
+        intersections: None,
+    })
+}
+
+fn has_function(ast: &AST, name: &str, params: usize) -> bool {
+    ast.iter_functions()
+        .any(|function| function.name == name && function.params.len() == params)
+}
+
+fn to_float(value: &Dynamic) -> Option<f32> {
+    value
+        .as_float()
+        .map(|value| value as f32)
+        .or_else(|_| value.as_int().map(|value| value as f32))
+        .ok()
+}
+
+fn to_color(value: Option<&Dynamic>) -> Option<Option<Color>> {
+    let value = match value {
+        Some(value) => value,
+        None => return Some(None),
+    };
+
+    let components: Vec<i64> = value
+        .read_lock::<Array>()?
+        .iter()
+        .map(|component| component.as_int().ok())
+        .collect::<Option<_>>()?;
+    CurveDefinition::color_from_components(&components).map(Some)
+}
+
+// Returns the radius, for points given as [x, y] it is their distance from the origin
+fn to_point(point: &mut Vector2f, angle: f32, value: Dynamic) -> Result<f32, String> {
+    if let Some(radius) = to_float(&value) {
+        return Ok(CurveDefinition::radius_to_point(point, angle, radius));
+    }
+
+    let coordinates: Option<Vec<f32>> = value
+        .read_lock::<Array>()
+        .and_then(|array| array.iter().map(to_float).collect());
+    match coordinates.as_deref() {
+        Some(&[x, y]) => {
+            point.x = x;
+            point.y = y;
+            Ok(x.hypot(y))
+        }
+        _ => Err(format!(
+            "expected a radius or [x, y], got {}",
+            value.type_name()
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scripts_stop_when_the_frame_budget_is_spent() {
+        let curve = load("fn point(angle) { let r = 0; for i in 0..2000 { r += 1; } r }").unwrap();
+        let mut point = Vector2f::default();
+        assert_eq!((curve.angle_to_point)(&mut point, 0.0), 2000.0);
+
+        curve.script_time.as_ref().unwrap().set(TIME_BUDGET);
+        assert_eq!((curve.angle_to_point)(&mut point, 1.0), 0.0);
+        let error = curve.errors.as_ref().unwrap().borrow_mut().take().unwrap();
+        assert_eq!(error, "point(1): script took over 2 s in one frame");
+    }
+}
@@ -0,0 +1,392 @@
+use std::fs;
+
+use sfml::graphics::{
+    Color, Font, PrimitiveType, RenderStates, RenderTarget, Text, Transformable, Vertex,
+};
+use sfml::system::{Vector2f, Vector2u};
+
+use crate::config::Config;
+use crate::data_array;
+use crate::legend;
+use crate::svg::Svg;
+
+const LABEL_SIZE: u32 = 14;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Scale {
+    Shared,
+    PerAxis,
+}
+
+pub struct Series {
+    pub name: String,
+    pub values: Vec<f32>,
+}
+
+pub struct SpiderChart {
+    pub scale: Scale,
+    pub max: Option<f32>,
+    pub rings: u32,
+    pub opacity: u8,
+    pub line_color: Color,
+    axes: Vec<String>,
+    series: Vec<Series>,
+    message: Option<String>,
+    grid: Vec<Vertex>,
+    fills: Vec<Vec<Vertex>>,
+    outlines: Vec<Vec<Vertex>>,
+    labels: Vec<(Vector2f, String)>,
+    size: Vector2u,
+}
+
+impl SpiderChart {
+    pub fn new() -> SpiderChart {
+        SpiderChart {
+            scale: Scale::Shared,
+            max: None,
+            rings: 5,
+            opacity: 80,
+            line_color: Color::rgba(255, 255, 255, 70),
+            axes: Vec::new(),
+            series: Vec::new(),
+            message: Some("No data, set 'file' in the [spider] section".to_owned()),
+            grid: Vec::new(),
+            fills: Vec::new(),
+            outlines: Vec::new(),
+            labels: Vec::new(),
+            size: (0, 0).into(),
+        }
+    }
+
+    pub fn from_config(config: &Config) -> SpiderChart {
+        let mut chart = Self::new();
+
+        if let Some(value) = config.get("spider", "scale") {
+            match value {
+                "shared" => chart.scale = Scale::Shared,
+                "axis" => chart.scale = Scale::PerAxis,
+                _ => eprintln!("{}: invalid spider scale \"{}\"", config.path, value),
+            }
+        }
+
+        if let Some(value) = config.get("spider", "max") {
+            match value.parse::<f32>() {
+                Ok(max) if max > 0.0 && max.is_finite() => chart.max = Some(max),
+                _ => eprintln!("{}: invalid spider max \"{}\"", config.path, value),
+            }
+        }
+
+        if let Some(value) = config.get("spider", "rings") {
+            match value.parse::<u32>() {
+                Ok(rings) if (1..=20).contains(&rings) => chart.rings = rings,
+                _ => eprintln!("{}: invalid spider rings \"{}\"", config.path, value),
+            }
+        }
+
+        if let Some(value) = config.get("spider", "opacity") {
+            match value.parse::<f32>() {
+                Ok(opacity) if (0.0..=1.0).contains(&opacity) => {
+                    chart.opacity = (opacity * 255.0).round() as u8
+                }
+                _ => eprintln!("{}: invalid spider opacity \"{}\"", config.path, value),
+            }
+        }
+
+        if let Some(path) = config.get("spider", "file") {
+            match fs::read_to_string(path)
+                .map_err(|error| error.to_string())
+                .and_then(|text| Self::parse(&text))
+            {
+                Ok((axes, series)) => {
+                    chart.axes = axes;
+                    chart.series = series;
+                    chart.message = None;
+                }
+                Err(error) => {
+                    let message = format!("{}: {}", path, error);
+                    eprintln!("{}", message);
+                    chart.message = Some(message);
+                }
+            }
+        }
+
+        chart
+    }
+
+    // The header row names the axes after a first cell for the series name,
+    // every other row is "name, value, value, ..."
+    pub fn parse(text: &str) -> Result<(Vec<String>, Vec<Series>), String> {
+        let delimiter = if text.contains('\t') { '\t' } else { ',' };
+        let mut rows = text
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+            .map(|(number, line)| {
+                let fields: Vec<&str> = line.split(delimiter).map(str::trim).collect();
+                (number, fields)
+            });
+
+        let (_, header) = rows.next().ok_or("empty file")?;
+        let axes: Vec<String> = header[1..].iter().map(|name| name.to_string()).collect();
+        if axes.len() < 3 {
+            return Err("need at least three axes".to_owned());
+        }
+
+        let mut series = Vec::new();
+        for (number, fields) in rows {
+            if fields.len() != axes.len() + 1 {
+                return Err(format!(
+                    "line {}: expected {} values, found {}",
+                    number,
+                    axes.len(),
+                    fields.len() - 1
+                ));
+            }
+            let values = fields[1..]
+                .iter()
+                .map(|value| {
+                    value
+                        .parse::<f32>()
+                        .ok()
+                        .filter(|value| value.is_finite())
+                        .ok_or_else(|| format!("line {}: invalid value '{}'", number, value))
+                })
+                .collect::<Result<_, _>>()?;
+            series.push(Series {
+                name: fields[0].to_owned(),
+                values,
+            });
+        }
+        if series.is_empty() {
+            return Err("no series found".to_owned());
+        }
+
+        Ok((axes, series))
+    }
+
+    // Value at the outer ring for every axis
+    pub fn axis_max(&self) -> Vec<f32> {
+        let column_max = |axis: usize| {
+            self.series
+                .iter()
+                .fold(0.0f32, |max, series| max.max(series.values[axis]))
+        };
+        let max = match self.scale {
+            Scale::Shared => {
+                let max = self
+                    .max
+                    .unwrap_or_else(|| (0..self.axes.len()).map(column_max).fold(0.0, f32::max));
+                vec![max; self.axes.len()]
+            }
+            Scale::PerAxis => (0..self.axes.len()).map(column_max).collect(),
+        };
+        max.into_iter()
+            .map(|max| if max > 0.0 { max } else { 1.0 })
+            .collect()
+    }
+
+    pub fn update(&mut self, size: Vector2u) {
+        if self.size != size {
+            self.rebuild(size);
+        }
+    }
+
+    pub fn rebuild(&mut self, size: Vector2u) {
+        self.size = size;
+        self.grid.clear();
+        self.fills.clear();
+        self.outlines.clear();
+        self.labels.clear();
+
+        let count = self.axes.len();
+        if count == 0 {
+            return;
+        }
+
+        let radius = data_array::get_radius(size, data_array::MARGIN);
+        let center = Vector2f::new(size.x as f32 / 2.0, size.y as f32 / 2.0);
+        let to_screen = |axis: usize, fraction: f32| {
+            let bearing = axis as f32 * 360.0 / count as f32;
+            let (sin, cos) = bearing.to_radians().sin_cos();
+            let distance = fraction * radius;
+            Vector2f::new(center.x + sin * distance, center.y - cos * distance)
+        };
+
+        for ring in 1..=self.rings {
+            let fraction = ring as f32 / self.rings as f32;
+            for axis in 0..count {
+                for point in [to_screen(axis, fraction), to_screen(axis + 1, fraction)] {
+                    self.grid
+                        .push(Vertex::with_pos_color(point, self.line_color));
+                }
+            }
+        }
+
+        let axis_max = self.axis_max();
+        for (axis, name) in self.axes.iter().enumerate() {
+            self.grid
+                .push(Vertex::with_pos_color(center, self.line_color));
+            self.grid.push(Vertex::with_pos_color(
+                to_screen(axis, 1.0),
+                self.line_color,
+            ));
+
+            let label = match self.scale {
+                Scale::Shared => name.clone(),
+                Scale::PerAxis => format!("{} ({})", name, axis_max[axis]),
+            };
+            self.labels.push((to_screen(axis, 1.1), label));
+        }
+        if self.scale == Scale::Shared {
+            for ring in 1..=self.rings {
+                let fraction = ring as f32 / self.rings as f32;
+                let value = axis_max[0] * fraction;
+                self.labels
+                    .push((to_screen(0, fraction), format!("{}", value)));
+            }
+        }
+
+        // The polygons are star-shaped around the center, so a fan fills them
+        for (i, series) in self.series.iter().enumerate() {
+            let color = legend::categorical(i);
+            let fill = Color {
+                a: self.opacity,
+                ..color
+            };
+
+            let points: Vec<Vector2f> = (0..=count)
+                .map(|axis| {
+                    let axis = axis % count;
+                    let fraction = (series.values[axis] / axis_max[axis]).clamp(0.0, 1.0);
+                    to_screen(axis, fraction)
+                })
+                .collect();
+
+            self.fills.push(
+                std::iter::once(center)
+                    .chain(points.iter().copied())
+                    .map(|point| Vertex::with_pos_color(point, fill))
+                    .collect(),
+            );
+            self.outlines.push(
+                points
+                    .into_iter()
+                    .map(|point| Vertex::with_pos_color(point, color))
+                    .collect(),
+            );
+        }
+    }
+
+    fn legend(&self) -> Vec<(Color, &str)> {
+        self.series
+            .iter()
+            .enumerate()
+            .map(|(i, series)| (legend::categorical(i), series.name.as_str()))
+            .collect()
+    }
+
+    pub fn draw(
+        &self,
+        render_target: &mut dyn RenderTarget,
+        background: Color,
+        font: Option<&Font>,
+    ) {
+        render_target.clear(background);
+        render_target.draw_primitives(&self.grid, PrimitiveType::LINES, &RenderStates::DEFAULT);
+
+        for (fill, outline) in self.fills.iter().zip(&self.outlines) {
+            render_target.draw_primitives(
+                fill,
+                PrimitiveType::TRIANGLE_FAN,
+                &RenderStates::DEFAULT,
+            );
+            render_target.draw_primitives(
+                outline,
+                PrimitiveType::LINE_STRIP,
+                &RenderStates::DEFAULT,
+            );
+        }
+
+        legend::draw(render_target, &self.legend(), self.size, background, font);
+
+        if let Some(font) = font {
+            let mut label = |position: Vector2f, text: &str, color: Color, centered: bool| {
+                let mut label = Text::new(text, font, LABEL_SIZE);
+                label.set_fill_color(color);
+                label.set_outline_color(background);
+                label.set_outline_thickness(1.5);
+                if centered {
+                    let bounds = label.local_bounds();
+                    label.set_origin((bounds.left + bounds.width / 2.0, LABEL_SIZE as f32 / 2.0));
+                }
+                label.set_position(position);
+                render_target.draw(&label);
+            };
+
+            for (position, text) in &self.labels {
+                label(*position, text, Color::WHITE, true);
+            }
+            if let Some(message) = &self.message {
+                label(
+                    Vector2f::new(10.0, self.size.y as f32 - 60.0),
+                    message,
+                    Color::RED,
+                    false,
+                );
+            }
+        }
+    }
+
+    pub fn draw_to_svg(&self, svg: &mut Svg) {
+        for line in self.grid.chunks(2) {
+            svg.polyline(line, self.line_color, 1.0);
+        }
+
+        for (fill, outline) in self.fills.iter().zip(&self.outlines) {
+            svg.polygon(outline, fill[0].color);
+            svg.polyline(outline, outline[0].color, 1.5);
+        }
+
+        // Roughly centered, SVG viewers use different fonts anyway
+        for (position, text) in &self.labels {
+            let width = text.chars().count() as f32 * LABEL_SIZE as f32 * 0.55;
+            svg.text(
+                position.x - width / 2.0,
+                position.y + LABEL_SIZE as f32 / 2.0,
+                LABEL_SIZE,
+                Color::WHITE,
+                text,
+            );
+        }
+
+        legend::draw_to_svg(svg, &self.legend(), self.size);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_and_scale_series() {
+        let text = "# cars\nmodel,speed,power,range\nA,8,3,400\nB,6,4,200\n";
+        let (axes, series) = SpiderChart::parse(text).unwrap();
+        assert_eq!(axes, ["speed", "power", "range"]);
+        assert_eq!(series[1].name, "B");
+        assert_eq!(series[1].values, [6.0, 4.0, 200.0]);
+
+        let mut chart = SpiderChart::new();
+        chart.axes = axes;
+        chart.series = series;
+        assert_eq!(chart.axis_max(), [400.0, 400.0, 400.0]);
+        chart.max = Some(10.0);
+        assert_eq!(chart.axis_max(), [10.0, 10.0, 10.0]);
+        chart.scale = Scale::PerAxis;
+        assert_eq!(chart.axis_max(), [8.0, 4.0, 400.0]);
+
+        assert!(SpiderChart::parse("model,a,b\nA,1,2\n").is_err());
+        assert!(SpiderChart::parse("model,a,b,c\nA,1,2\n").is_err());
+        assert!(SpiderChart::parse("model,a,b,c\nA,1,x,2\n").is_err());
+    }
+}
@@ -0,0 +1,241 @@
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::fs;
+use std::io::{self, BufRead, BufReader};
+use std::net::{TcpListener, UdpSocket};
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::thread;
+
+use crate::config::Config;
+
+// Records waiting for the viewer, which stops polling while paused. Once
+// this many wait the reader blocks instead of buffering without bound
+const CHANNEL_CAPACITY: usize = 65536;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Record {
+    pub angle: f32,
+    pub distance: f32,
+    pub intensity: Option<f32>,
+}
+
+impl Record {
+    // "angle distance [intensity]", separated by whitespace or commas,
+    // anything else (headers, comments, garbage) is skipped
+    pub fn parse(line: &str) -> Option<Record> {
+        let mut fields = line
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|field| !field.is_empty())
+            .map(|field| field.parse::<f32>().ok().filter(|value| value.is_finite()));
+
+        let angle = fields.next()??;
+        let distance = fields.next()??;
+        let intensity = match fields.next() {
+            Some(intensity) => Some(intensity?),
+            None => None,
+        };
+        if fields.next().is_some() {
+            return None;
+        }
+
+        Some(Record {
+            angle,
+            distance,
+            intensity,
+        })
+    }
+}
+
+pub struct Stream {
+    pub source: String,
+    receiver: Receiver<Record>,
+    pub records: VecDeque<Record>,
+    pub range: f32,
+    pub auto_range: bool,
+    pub max_intensity: f32,
+    pub records_per_frame: usize,
+    pub frozen: bool,
+    pub received: u64,
+    snapshot_count: u32,
+}
+
+impl Stream {
+    // "-" reads stdin, udp://host:port binds a socket, tcp://host:port
+    // listens and reads every connection in turn
+    pub fn open(source: &str) -> Result<Stream, String> {
+        let (sender, receiver) = mpsc::sync_channel(CHANNEL_CAPACITY);
+
+        if source == "-" {
+            thread::spawn(move || read_lines(io::stdin().lock(), &sender));
+        } else if let Some(address) = source.strip_prefix("udp://") {
+            let socket = UdpSocket::bind(address).map_err(|error| error.to_string())?;
+            thread::spawn(move || read_datagrams(socket, &sender));
+        } else if let Some(address) = source.strip_prefix("tcp://") {
+            let listener = TcpListener::bind(address).map_err(|error| error.to_string())?;
+            thread::spawn(move || {
+                for connection in listener.incoming() {
+                    match connection {
+                        Ok(connection) => {
+                            if !read_lines(BufReader::new(connection), &sender) {
+                                break;
+                            }
+                        }
+                        Err(error) => eprintln!("stream: {}", error),
+                    }
+                }
+            });
+        } else {
+            return Err("expected '-', udp://host:port or tcp://host:port".to_owned());
+        }
+
+        Ok(Stream {
+            source: source.to_owned(),
+            receiver,
+            records: VecDeque::new(),
+            range: 1.0,
+            auto_range: true,
+            max_intensity: 0.0,
+            records_per_frame: 0,
+            frozen: false,
+            received: 0,
+            snapshot_count: 0,
+        })
+    }
+
+    pub fn load(&mut self, config: &Config) {
+        if let Some(value) = config.get("stream", "range") {
+            match value {
+                "auto" => self.auto_range = true,
+                _ => match value.parse::<f32>() {
+                    Ok(range) if range > 0.0 && range.is_finite() => {
+                        self.range = range;
+                        self.auto_range = false;
+                    }
+                    _ => eprintln!("{}: invalid stream range \"{}\"", config.path, value),
+                },
+            }
+        }
+
+        if let Some(value) = config.get("stream", "records_per_frame") {
+            match value.parse::<usize>() {
+                Ok(records_per_frame) => self.records_per_frame = records_per_frame,
+                _ => eprintln!(
+                    "{}: invalid stream records_per_frame \"{}\"",
+                    config.path, value
+                ),
+            }
+        }
+    }
+
+    // Records that arrived since the last call, dropped while frozen
+    pub fn poll(&mut self) -> Vec<Record> {
+        let limit = match self.records_per_frame {
+            0 => usize::MAX,
+            limit => limit,
+        };
+        let records: Vec<Record> = self.receiver.try_iter().take(limit).collect();
+        self.received += records.len() as u64;
+
+        if self.frozen {
+            Vec::new()
+        } else {
+            records
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.records.clear();
+        self.max_intensity = 0.0;
+        if self.auto_range {
+            self.range = 1.0;
+        }
+    }
+
+    pub fn next_snapshot_name(&mut self) -> String {
+        self.snapshot_count += 1;
+        format!("scan-{:03}", self.snapshot_count)
+    }
+
+    pub fn save_records(&self, filename: &str) -> io::Result<()> {
+        let mut text = "angle,distance,intensity\n".to_owned();
+        for record in &self.records {
+            match record.intensity {
+                Some(intensity) => {
+                    writeln!(text, "{},{},{}", record.angle, record.distance, intensity)
+                }
+                None => writeln!(text, "{},{},", record.angle, record.distance),
+            }
+            .unwrap();
+        }
+        fs::write(filename, text)
+    }
+}
+
+// Returns false once the viewer is gone
+fn read_lines(reader: impl BufRead, sender: &SyncSender<Record>) -> bool {
+    for line in reader.lines() {
+        match line {
+            Ok(line) => {
+                if let Some(record) = Record::parse(&line) {
+                    if sender.send(record).is_err() {
+                        return false;
+                    }
+                }
+            }
+            Err(error) => {
+                eprintln!("stream: {}", error);
+                break;
+            }
+        }
+    }
+    true
+}
+
+fn read_datagrams(socket: UdpSocket, sender: &SyncSender<Record>) {
+    let mut buffer = [0; 65536];
+    loop {
+        match socket.recv(&mut buffer) {
+            Ok(length) => {
+                let text = String::from_utf8_lossy(&buffer[..length]);
+                for record in text.lines().filter_map(Record::parse) {
+                    if sender.send(record).is_err() {
+                        return;
+                    }
+                }
+            }
+            Err(error) => {
+                eprintln!("stream: {}", error);
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_records() {
+        assert_eq!(
+            Record::parse("12.5, 3.25"),
+            Some(Record {
+                angle: 12.5,
+                distance: 3.25,
+                intensity: None
+            })
+        );
+        assert_eq!(
+            Record::parse("  90\t1.5 200 "),
+            Some(Record {
+                angle: 90.0,
+                distance: 1.5,
+                intensity: Some(200.0)
+            })
+        );
+        assert_eq!(Record::parse("# angle distance"), None);
+        assert_eq!(Record::parse("1 2 3 4"), None);
+        assert_eq!(Record::parse("1 NaN"), None);
+        assert_eq!(Record::parse(""), None);
+    }
+}
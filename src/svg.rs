@@ -0,0 +1,140 @@
+use std::fmt::Write;
+use std::fs;
+use std::io;
+
+use sfml::graphics::{Color, Vertex};
+use sfml::system::Vector2f;
+
+use crate::region::FillRule;
+
+pub struct Svg {
+    width: u32,
+    height: u32,
+    body: String,
+}
+
+impl Svg {
+    pub fn new(width: u32, height: u32, background: Color) -> Svg {
+        let mut svg = Svg {
+            width,
+            height,
+            body: String::new(),
+        };
+        svg.rect(0.0, 0.0, width as f32, height as f32, background);
+        svg
+    }
+
+    pub fn rect(&mut self, x: f32, y: f32, width: f32, height: f32, color: Color) {
+        writeln!(
+            self.body,
+            r#"<rect x="{:.2}" y="{:.2}" width="{:.2}" height="{:.2}" {}/>"#,
+            x,
+            y,
+            width,
+            height,
+            Self::paint("fill", color)
+        )
+        .unwrap();
+    }
+
+    pub fn polyline(&mut self, vertecies: &[Vertex], color: Color, width: f32) {
+        if vertecies.is_empty() {
+            return;
+        }
+
+        write!(self.body, r#"<polyline fill="none" points=""#).unwrap();
+        for vertex in vertecies {
+            write!(
+                self.body,
+                "{:.2},{:.2} ",
+                vertex.position.x, vertex.position.y
+            )
+            .unwrap();
+        }
+        writeln!(
+            self.body,
+            r#"" stroke-width="{:.2}" stroke-linejoin="round" {}/>"#,
+            width,
+            Self::paint("stroke", color)
+        )
+        .unwrap();
+    }
+
+    pub fn polygon(&mut self, vertecies: &[Vertex], color: Color) {
+        if vertecies.is_empty() {
+            return;
+        }
+
+        write!(self.body, r#"<polygon points=""#).unwrap();
+        for vertex in vertecies {
+            write!(
+                self.body,
+                "{:.2},{:.2} ",
+                vertex.position.x, vertex.position.y
+            )
+            .unwrap();
+        }
+        writeln!(self.body, r#"" {}/>"#, Self::paint("fill", color)).unwrap();
+    }
+
+    // Closed path filled by the rule, where overlapping loops are left out or not
+    pub fn path(&mut self, points: &[Vector2f], color: Color, rule: FillRule) {
+        if points.is_empty() {
+            return;
+        }
+
+        write!(self.body, r#"<path d=""#).unwrap();
+        for (i, point) in points.iter().enumerate() {
+            let command = if i == 0 { 'M' } else { 'L' };
+            write!(self.body, "{}{:.2},{:.2} ", command, point.x, point.y).unwrap();
+        }
+        writeln!(
+            self.body,
+            r#"Z" fill-rule="{}" {}/>"#,
+            rule.name(),
+            Self::paint("fill", color)
+        )
+        .unwrap();
+    }
+
+    pub fn text(&mut self, x: f32, y: f32, size: u32, color: Color, text: &str) {
+        let text = text
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;");
+        writeln!(
+            self.body,
+            r#"<text x="{:.2}" y="{:.2}" font-size="{}" font-family="sans-serif" {}>{}</text>"#,
+            x,
+            y,
+            size,
+            Self::paint("fill", color),
+            text
+        )
+        .unwrap();
+    }
+
+    pub fn save_to_file(&self, filename: &str) -> io::Result<()> {
+        fs::write(
+            filename,
+            format!(
+                concat!(
+                    r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{1}" viewBox="0 0 {0} {1}">"#,
+                    "\n{2}</svg>\n"
+                ),
+                self.width, self.height, self.body
+            ),
+        )
+    }
+
+    fn paint(attribute: &str, color: Color) -> String {
+        format!(
+            r#"{0}="rgb({1},{2},{3})" {0}-opacity="{4:.3}""#,
+            attribute,
+            color.r,
+            color.g,
+            color.b,
+            color.a as f32 / 255.0
+        )
+    }
+}
@@ -0,0 +1,186 @@
+use std::f32::consts::TAU;
+
+use sfml::graphics::{Color, PrimitiveType, RenderStates, RenderTarget, Vertex};
+use sfml::system::{Vector2f, Vector2u};
+
+use crate::data_array;
+
+// Half of the central difference step in degrees, small enough for curves with
+// many petals per turn and large enough for f32 second differences
+const STEP: f32 = 1.0;
+const ARC_RADIUS: f32 = 30.0;
+const VECTOR_LENGTH: f32 = 60.0;
+const ARROW_SIZE: f32 = 8.0;
+// Circles wider than this many view radii are drawn as the straight line they look like
+const MAX_CIRCLE: f32 = 50.0;
+
+const RADIUS_COLOR: Color = Color::rgba(255, 255, 255, 160);
+const TANGENT_COLOR: Color = Color::rgb(96, 255, 96);
+const NORMAL_COLOR: Color = Color::rgb(255, 160, 64);
+const CIRCLE_COLOR: Color = Color::rgba(64, 200, 255, 200);
+
+// Point of the curve with its numeric derivatives by the angle in radians
+pub struct TracePoint {
+    pub angle: f32,
+    pub r: f32,
+    pub dr: f32,
+    pub point: Vector2f,
+    pub velocity: Vector2f,
+    pub acceleration: Vector2f,
+}
+
+impl TracePoint {
+    pub fn at(angle_to_point: &dyn Fn(&mut Vector2f, f32) -> f32, angle: f32) -> TracePoint {
+        let h = STEP.to_radians();
+        let mut before = Vector2f::default();
+        let mut point = Vector2f::default();
+        let mut after = Vector2f::default();
+        let r_before = angle_to_point(&mut before, angle - STEP);
+        let r = angle_to_point(&mut point, angle);
+        let r_after = angle_to_point(&mut after, angle + STEP);
+
+        TracePoint {
+            angle,
+            r,
+            dr: (r_after - r_before) / (2.0 * h),
+            point,
+            velocity: (after - before) / (2.0 * h),
+            acceleration: (after - point * 2.0 + before) / (h * h),
+        }
+    }
+
+    pub fn tangent(&self) -> Vector2f {
+        self.velocity / self.velocity.x.hypot(self.velocity.y)
+    }
+
+    // Tangent turned a quarter turn counterclockwise
+    pub fn normal(&self) -> Vector2f {
+        let tangent = self.tangent();
+        Vector2f::new(-tangent.y, tangent.x)
+    }
+
+    // Positive where the curve turns counterclockwise
+    pub fn curvature(&self) -> f32 {
+        let (v, a) = (self.velocity, self.acceleration);
+        (v.x * a.y - v.y * a.x) / v.x.hypot(v.y).powi(3)
+    }
+
+    pub fn center(&self) -> Vector2f {
+        self.point + self.normal() / self.curvature()
+    }
+
+    // Radius vector, angle arc, tangent and normal, and the osculating
+    // circle, with the curve's unit space placed on screen like the curve
+    pub fn draw(
+        &self,
+        target: &mut dyn RenderTarget,
+        origin: Vector2f,
+        size: Vector2u,
+        margin: f32,
+    ) {
+        if !(self.point.x.is_finite() && self.point.y.is_finite()) {
+            return;
+        }
+        let radius = data_array::get_radius(size, margin);
+        let to_screen = |mut point: Vector2f| {
+            data_array::unit_to_screen_point(&mut point, size, margin);
+            point
+        };
+        let direction = |vector: Vector2f| Vector2f::new(vector.x, -vector.y);
+        let origin = to_screen(origin);
+        let point = to_screen(self.point);
+
+        let mut lines = vec![
+            Vertex::with_pos_color(origin, RADIUS_COLOR),
+            Vertex::with_pos_color(point, RADIUS_COLOR),
+        ];
+        let tangent = self.tangent();
+        if tangent.x.is_finite() && tangent.y.is_finite() {
+            arrow(&mut lines, point, direction(tangent), TANGENT_COLOR);
+            arrow(&mut lines, point, direction(self.normal()), NORMAL_COLOR);
+        }
+        target.draw_primitives(&lines, PrimitiveType::LINES, &RenderStates::DEFAULT);
+
+        // Counterclockwise from the polar axis, a full turn at most
+        let sweep = self.angle.rem_euclid(360.0).to_radians();
+        let arc: Vec<Vertex> = (0..=64)
+            .map(|i| {
+                let (sin, cos) = (sweep * i as f32 / 64.0).sin_cos();
+                let position = origin + Vector2f::new(cos, -sin) * ARC_RADIUS;
+                Vertex::with_pos_color(position, RADIUS_COLOR)
+            })
+            .collect();
+        target.draw_primitives(&arc, PrimitiveType::LINE_STRIP, &RenderStates::DEFAULT);
+
+        let circle_radius = self.curvature().recip().abs();
+        if circle_radius.is_finite() && circle_radius < MAX_CIRCLE {
+            let center = to_screen(self.center());
+            let circle: Vec<Vertex> = (0..=120)
+                .map(|i| {
+                    let (sin, cos) = (TAU * i as f32 / 120.0).sin_cos();
+                    let position = center + Vector2f::new(cos, sin) * circle_radius * radius;
+                    Vertex::with_pos_color(position, CIRCLE_COLOR)
+                })
+                .collect();
+            target.draw_primitives(&circle, PrimitiveType::LINE_STRIP, &RenderStates::DEFAULT);
+        }
+    }
+}
+
+// Line from `start` along the unit `direction` with a head at its end
+fn arrow(lines: &mut Vec<Vertex>, start: Vector2f, direction: Vector2f, color: Color) {
+    let end = start + direction * VECTOR_LENGTH;
+    let side = Vector2f::new(-direction.y, direction.x) * (ARROW_SIZE / 2.0);
+    let back = end - direction * ARROW_SIZE;
+    for (from, to) in [(start, end), (end, back + side), (end, back - side)] {
+        lines.push(Vertex::with_pos_color(from, color));
+        lines.push(Vertex::with_pos_color(to, color));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curve_file::CurveDefinition;
+
+    fn assert_close(actual: Vector2f, expected: Vector2f) {
+        assert!(
+            (actual - expected).x.hypot((actual - expected).y) < 1e-3,
+            "{:?} is not {:?}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn circle_curves_around_its_center() {
+        let circle = TracePoint::at(
+            &|point, angle| CurveDefinition::radius_to_point(point, angle, 2.0),
+            90.0,
+        );
+        assert_close(circle.tangent(), Vector2f::new(-1.0, 0.0));
+        assert_close(circle.normal(), Vector2f::new(0.0, -1.0));
+        assert_close(circle.center(), Vector2f::default());
+        assert!((circle.curvature() - 0.5).abs() < 1e-3);
+        assert!(circle.dr.abs() < 1e-3);
+    }
+
+    #[test]
+    fn rose_tip_matches_polar_curvature() {
+        // r = cos(2t) at t = 0: r = 1, r' = 0, r'' = -4, so the curvature
+        // (r^2 + 2r'^2 - r r'') / (r^2 + r'^2)^(3/2) is 5
+        let rose = TracePoint::at(
+            &|point, angle| {
+                let r = (2.0 * angle.to_radians()).cos();
+                CurveDefinition::radius_to_point(point, angle, r)
+            },
+            0.0,
+        );
+        assert!(
+            (rose.curvature() - 5.0).abs() < 1e-2,
+            "{}",
+            rose.curvature()
+        );
+        assert_close(rose.center(), Vector2f::new(0.8, 0.0));
+    }
+}
@@ -1,4 +1,8 @@
-use std::fs::create_dir_all;
+use std::fs::{self, create_dir_all};
+use std::mem;
+use std::ops::Range;
+use std::path::Path;
+use std::time::Duration;
 
 use sfml::graphics::{
     Color, FloatRect, Font, PrimitiveType, RenderStates, RenderTarget, RenderTexture, RenderWindow,
@@ -10,8 +14,84 @@ use sfml::SfBox;
 
 use bitflags::bitflags;
 
+use crate::between::BetweenCurves;
+use crate::config::Config;
+use crate::curve_file::{CurveDefinition, CurveWatcher, ErrorSlot, TimeSlot};
+use crate::curve_stats::CurveStats;
+use crate::data_array;
+use crate::frame_times::FrameTimes;
+use crate::grid::RoseGrid;
+use crate::intersections::Intersections;
+use crate::keymap::{Action, Keymap};
+use crate::plugins::{self, Plugin};
+use crate::radar::Radar;
+use crate::radial_axis::RadialAxis;
+use crate::region::{Fill, FillCache};
+use crate::riemann::RiemannSum;
+use crate::sample_export::{self, ExportFormat, SampleExport};
+use crate::spider::SpiderChart;
+use crate::stream::{Record, Stream};
+use crate::svg::Svg;
+use crate::tracing::TracePoint;
+use crate::wind_rose::WindRose;
+
+// Smallest character size the help screen shrinks to
+const MIN_HELP_SIZE: u32 = 8;
+// Smallest step Ctrl+- goes down to, a step of 0 would never finish a period
+const MIN_ANGLE_DELTA: f32 = 0.01;
+
+const EMBEDDED_FONT: &[u8] = include_bytes!("../assets/DejaVuSansMono.ttf");
+
+const SYSTEM_FONTS: [&str; 8] = [
+    "/usr/share/fonts/truetype/dejavu/DejaVuSansMono.ttf",
+    "/usr/share/fonts/TTF/DejaVuSansMono.ttf",
+    "/usr/share/fonts/dejavu/DejaVuSansMono.ttf",
+    "/usr/share/fonts/truetype/liberation/LiberationMono-Regular.ttf",
+    "/System/Library/Fonts/Monaco.ttf",
+    "/Library/Fonts/Arial.ttf",
+    "C:\\Windows\\Fonts\\consola.ttf",
+    "C:\\Windows\\Fonts\\arial.ttf",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FramePacing {
+    Capped,
+    Uncapped,
+    Vsync,
+}
+
+impl FramePacing {
+    pub fn name(self) -> &'static str {
+        match self {
+            FramePacing::Capped => "capped",
+            FramePacing::Uncapped => "uncapped",
+            FramePacing::Vsync => "vsync",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<FramePacing> {
+        match name {
+            "capped" => Some(FramePacing::Capped),
+            "uncapped" => Some(FramePacing::Uncapped),
+            "vsync" => Some(FramePacing::Vsync),
+            _ => None,
+        }
+    }
+
+    pub fn next(self) -> FramePacing {
+        match self {
+            FramePacing::Capped => FramePacing::Uncapped,
+            FramePacing::Uncapped => FramePacing::Vsync,
+            FramePacing::Vsync => FramePacing::Capped,
+        }
+    }
+}
+
+// Writes the point for an angle and returns its signed radius
+pub type AngleToPoint = Box<dyn Fn(&mut Vector2f, f32) -> f32>;
+
 bitflags! {
-    pub struct Flags: u8 {
+    pub struct Flags: u32 {
         const NO_DRAW = 1 << 0;
         const FULLSCREEN = 1 << 1;
         const PAUSE = 1 << 2;
@@ -20,23 +100,54 @@ bitflags! {
         const SHOW_CURSOR = 1 << 5;
         const RENDER_ANIMATION = 1 << 6;
         const NO_CUTOFF = 1 << 7;
+        const GRID = 1 << 8;
+        const GRID_LABELS = 1 << 9;
+        const DRAW_HELP = 1 << 10;
+        const RADAR = 1 << 11;
+        const WIND_ROSE = 1 << 12;
+        const SPIDER = 1 << 13;
+        const TRACING = 1 << 14;
+        const FILL = 1 << 15;
+        const RIEMANN = 1 << 16;
     }
 }
 
+// What draw_frame puts on screen from the bottom up: filled triangles,
+// outlines as pairs of line ends and the lines of the curve
+pub struct Layers {
+    pub fill: Vec<Vertex>,
+    pub outlines: Vec<Vertex>,
+    pub segments: Vec<Range<usize>>,
+}
+
 pub struct App {
     pub fps_clock: SfBox<Clock>,
+    pub frame_times: FrameTimes,
     pub ctx_settings: ContextSettings,
 
     pub flags: Flags,
+    pub config: Config,
+    pub keymap: Keymap,
+    pub sample_export: SampleExport,
+    pub axis: RadialAxis,
+    pub fill: Fill,
+    fill_cache: FillCache,
+    pub between: Option<BetweenCurves>,
+    pub intersections: Option<Intersections>,
+    pub riemann: RiemannSum,
+    pub stats: Option<CurveStats>,
     pub font: Option<SfBox<Font>>,
+    pub font_path: Option<String>,
 
     pub debug_text: String,
 
     pub background: Color,
+    pub color: Color,
 
     pub window: Option<RenderWindow>,
     pub size: Vector2u,
     pub fps_limit: u32,
+    pub frame_pacing: FramePacing,
 
     pub angle: f32,
     pub angle_limit: f32,
@@ -46,7 +157,25 @@ pub struct App {
     pub desired_count: usize,
 
     pub plugin_init: fn(&mut Self),
-    pub plgin_angle_to_point: fn(point: &mut sfml::system::Vector2f, angle: f32),
+    pub plgin_angle_to_point: AngleToPoint,
+
+    pub curve_name: String,
+    pub curve_watcher: Option<CurveWatcher>,
+    pub curve_error: Option<String>,
+    curve_errors: Option<ErrorSlot>,
+    script_time: Option<TimeSlot>,
+
+    pub stream: Option<Stream>,
+
+    pub plugin_directory: Option<String>,
+    pub plugins: Vec<Plugin>,
+    plugin_index: Option<usize>,
+    builtin_angle_to_point: Option<AngleToPoint>,
+
+    pub grid: RoseGrid,
+    pub radar: Radar,
+    pub wind_rose: WindRose,
+    pub spider: SpiderChart,
 
     render_texture: Option<RenderTexture>,
     pub render_texture_size: Vector2u,
@@ -62,9 +191,21 @@ impl App {
     pub fn new() -> App {
         App {
             fps_clock: Clock::start(),
+            frame_times: FrameTimes::new(120),
             ctx_settings: ContextSettings::default(),
             flags: Flags::empty(),
+            config: Config::new("polar-graphs.ini"),
+            keymap: Keymap::default(),
+            sample_export: SampleExport::default(),
+            axis: RadialAxis::default(),
+            fill: Fill::default(),
+            fill_cache: FillCache::default(),
+            between: None,
+            intersections: None,
+            riemann: RiemannSum::default(),
+            stats: None,
             font: None,
+            font_path: None,
             debug_text: String::new(),
             background: Color {
                 r: 0,
@@ -72,20 +213,41 @@ impl App {
                 b: 25,
                 a: 255,
             },
+            color: Color::WHITE,
             window: None,
             size: (800, 600).into(),
             fps_limit: 60,
+            frame_pacing: FramePacing::Capped,
             angle: 0.0,
             angle_limit: 360.0,
             angle_delta: 1.0,
             vertecies: Vec::new(),
             desired_count: 361,
             plugin_init: |app| app.angle_limit = 360.0,
-            plgin_angle_to_point: |point, angle| {
+            plgin_angle_to_point: Box::new(|point, angle| {
                 let (sin, cos) = angle.to_radians().sin_cos();
                 point.x = cos;
                 point.y = sin;
-            },
+                1.0
+            }),
+
+            curve_name: "built-in".to_owned(),
+            curve_watcher: None,
+            curve_error: None,
+            curve_errors: None,
+            script_time: None,
+
+            stream: None,
+
+            plugin_directory: None,
+            plugins: Vec::new(),
+            plugin_index: None,
+            builtin_angle_to_point: None,
+
+            grid: RoseGrid::new(7, 9),
+            radar: Radar::synthetic(12, 1),
+            wind_rose: WindRose::new(),
+            spider: SpiderChart::new(),
 
             render_texture: None,
             render_texture_size: (1024, 1024).into(),
@@ -96,18 +258,61 @@ impl App {
 
     pub fn init(&mut self, full: bool) {
         if full {
-            self.flags = Flags::SHOW_CURSOR;
-
-            if let Some(font) = Font::from_file("font.ttf") {
-                self.font = Some(font);
-            } else {
+            self.flags = Flags::SHOW_CURSOR | Flags::GRID_LABELS;
+
+            self.config = Config::load(&self.config.path);
+            self.keymap = Keymap::default();
+            self.keymap.load(&self.config);
+            self.sample_export = SampleExport::default();
+            self.sample_export.load(&self.config);
+            self.axis = RadialAxis::default();
+            self.axis.load(&self.config);
+            self.fill.load(&self.config);
+            if let Some(stream) = self.stream.as_mut() {
+                stream.load(&self.config);
+            }
+            self.radar = Radar::from_config(&self.config);
+            self.wind_rose = WindRose::from_config(&self.config);
+            self.spider = SpiderChart::from_config(&self.config);
+            self.load_frame_pacing();
+
+            self.font = self.load_font();
+            if self.font.is_none() {
+                eprintln!("No usable font found, debug and help screens are disabled");
                 self.flags |= Flags::FONT_FAILURE;
             }
 
+            if let Some(angle_to_point) = self.builtin_angle_to_point.take() {
+                self.plgin_angle_to_point = angle_to_point;
+                self.curve_name = "built-in".to_owned();
+                self.between = None;
+                self.intersections = None;
+            }
+            self.plugin_index = None;
+            // Loading a library runs its code, so only a directory the user names is searched
+            self.plugins = match self
+                .plugin_directory
+                .as_deref()
+                .or_else(|| self.config.get("viewer", "plugins"))
+            {
+                Some(directory) => plugins::discover(directory),
+                None => Vec::new(),
+            };
+
             (self.plugin_init)(self);
             self.resize_data_array();
             self.reset_data_array();
 
+            if self.curve_watcher.is_none() {
+                if let Some(path) = self.config.get("viewer", "curve") {
+                    self.curve_watcher = Some(CurveWatcher::new(path));
+                }
+            }
+            if let Some(curve_watcher) = self.curve_watcher.as_mut() {
+                curve_watcher.reset();
+                self.poll_curve_file();
+            }
+
             self.ctx_settings.antialiasing_level = 8;
         }
 
@@ -132,10 +337,49 @@ impl App {
             },
             &self.ctx_settings,
         );
-        window.set_framerate_limit(self.fps_limit);
         window.set_mouse_cursor_visible(self.flags.contains(Flags::SHOW_CURSOR));
 
         self.window = Some(window);
+        self.apply_frame_pacing();
+    }
+
+    fn load_font(&self) -> Option<SfBox<Font>> {
+        let path = self
+            .font_path
+            .as_deref()
+            .or_else(|| self.config.get("viewer", "font"));
+
+        match path {
+            Some(path) => match Font::from_file(path) {
+                Some(font) => return Some(font),
+                None => eprintln!("Failed to load font from {}", path),
+            },
+            None => {
+                if Path::new("font.ttf").exists() {
+                    match Font::from_file("font.ttf") {
+                        Some(font) => return Some(font),
+                        None => eprintln!("Failed to load font from font.ttf"),
+                    }
+                }
+            }
+        }
+
+        // SAFETY: the embedded buffer is 'static and thus outlives the font
+        match unsafe { Font::from_memory(EMBEDDED_FONT) } {
+            Some(font) => return Some(font),
+            None => eprintln!("Failed to load embedded font"),
+        }
+
+        for path in SYSTEM_FONTS {
+            if Path::new(path).exists() {
+                match Font::from_file(path) {
+                    Some(font) => return Some(font),
+                    None => eprintln!("Failed to load font from {}", path),
+                }
+            }
+        }
+
+        None
     }
 
     pub fn run(&mut self) {
@@ -144,6 +388,10 @@ impl App {
         }
 
         'main_loop: while self.window.as_ref().unwrap().is_open() {
+            // Scripts get a fresh time budget every frame
+            if let Some(script_time) = &self.script_time {
+                script_time.set(Duration::ZERO);
+            }
             {
                 while let Some(event) = self.window.as_mut().unwrap().poll_event() {
                     match event {
@@ -167,10 +415,11 @@ impl App {
                             let new_height = height.max(300);
 
                             if !self.flags.contains(Flags::RENDER_ANIMATION) {
-                                Self::rescale_data_array(
+                                data_array::rescale(
                                     &mut self.vertecies,
                                     self.size,
                                     (new_width, new_height).into(),
+                                    self.axis.margin,
                                 );
                             }
 
@@ -199,6 +448,7 @@ impl App {
                 }
             } else {
                 self.size = self.window.as_ref().unwrap().size();
+                self.poll_curve_file();
 
                 if !self.flags.contains(Flags::PAUSE) {
                     self.request_update();
@@ -216,124 +466,190 @@ impl App {
 
     pub fn process_key(&mut self, code: Key, ctrl: bool, shift: bool, alt: bool) -> bool {
         if !self.flags.contains(Flags::RENDER_ANIMATION) {
-            match code {
-                Key::Escape => {
-                    self.close();
-                    return false;
-                }
-                Key::Space => self.flags.toggle(Flags::PAUSE),
-                Key::F3 => self.flags.toggle(Flags::DRAW_GUI),
-                Key::F5 => self.init(!shift),
-                Key::H => {
-                    self.flags.toggle(Flags::SHOW_CURSOR);
-                    self.window
-                        .as_mut()
-                        .unwrap()
-                        .set_mouse_cursor_visible(self.flags.contains(Flags::SHOW_CURSOR));
-                }
-                Key::G => {
-                    self.prepare_render_texture();
-                    match create_dir_all("out") {
-                        Ok(()) => self.flags.insert(Flags::RENDER_ANIMATION),
-                        Err(error) => eprintln!("{}", error),
-                    }
-                }
-                Key::F2 => {
-                    self.desired_count = (self.angle_limit / self.angle_delta).round() as usize + 1;
-                    self.prepare_render_texture();
-                    let is_no_cutoff = self.flags.contains(Flags::NO_CUTOFF);
-                    self.flags.insert(Flags::NO_CUTOFF);
-                    self.disable_cutoff();
-                    self.draw_frame_to_texture("frame.png");
-                    self.flags.set(Flags::NO_CUTOFF, is_no_cutoff);
-                    self.size = self.window.as_ref().unwrap().size();
-                    Self::rescale_data_array(
-                        &mut self.vertecies,
-                        self.render_texture_size,
-                        self.size,
-                    );
-                }
-                Key::C => self.reset_data_array(),
-                Key::S => {
-                    while self.angle < self.angle_limit {
-                        self.angle += self.angle_delta;
-                        self.update_data_array();
-                    }
-                }
-                Key::F | Key::F11 => {
-                    self.flags.toggle(Flags::FULLSCREEN);
-                    self.init(false);
-                    Self::rescale_data_array(
-                        &mut self.vertecies,
-                        self.size,
-                        self.window.as_ref().unwrap().size(),
-                    );
-                }
-                Key::N => {
-                    self.flags.toggle(Flags::NO_CUTOFF);
-                    self.disable_cutoff();
-                }
-                Key::RBracket => {
-                    if self.ctx_settings.antialiasing_level < 16 {
-                        self.ctx_settings.antialiasing_level += 1;
-                    }
+            if let Some(action) = self.keymap.action(code, ctrl, shift, alt) {
+                return self.process_action(action, alt);
+            }
+        }
+        true
+    }
+
+    pub fn process_action(&mut self, action: Action, alt: bool) -> bool {
+        match action {
+            Action::Close => {
+                self.close();
+                return false;
+            }
+            Action::Pause => self.flags.toggle(Flags::PAUSE),
+            Action::ToggleHelp => {
+                self.flags.toggle(Flags::DRAW_HELP);
+                if self.flags.contains(Flags::DRAW_HELP | Flags::FONT_FAILURE) {
+                    print!("{}", self.keymap.help_text(1));
                 }
-                Key::LBracket => {
-                    if self.ctx_settings.antialiasing_level > 0 {
-                        self.ctx_settings.antialiasing_level -= 1;
-                    }
+            }
+            Action::ToggleDebug => self.flags.toggle(Flags::DRAW_GUI),
+            Action::Reinit => self.init(true),
+            Action::RecreateWindow => self.init(false),
+            Action::ToggleCursor => {
+                self.flags.toggle(Flags::SHOW_CURSOR);
+                self.window
+                    .as_mut()
+                    .unwrap()
+                    .set_mouse_cursor_visible(self.flags.contains(Flags::SHOW_CURSOR));
+            }
+            Action::ToggleGrid => self.flags.toggle(Flags::GRID),
+            Action::ToggleGridLabels => self.flags.toggle(Flags::GRID_LABELS),
+            Action::ToggleRadar => {
+                self.flags.toggle(Flags::RADAR);
+                self.radar.clear();
+            }
+            Action::ToggleWindRose => self.flags.toggle(Flags::WIND_ROSE),
+            Action::ToggleSpiderChart => self.flags.toggle(Flags::SPIDER),
+            Action::ToggleTracing => self.flags.toggle(Flags::TRACING),
+            Action::ToggleFill => self.flags.toggle(Flags::FILL),
+            Action::CycleFillRule => {
+                self.fill.rule = self.fill.rule.next();
+            }
+            Action::ToggleRiemannSum => {
+                self.flags.toggle(Flags::RIEMANN);
+                self.riemann.set_target(self.riemann.target);
+            }
+            Action::CycleRiemannRule => self.riemann.rule = self.riemann.rule.next(),
+            Action::RiemannSectorsUp => {
+                let target = self.riemann.target + Self::get_shift_multiplier() as usize;
+                self.set_riemann_target(target);
+            }
+            Action::RiemannSectorsDown => {
+                let delta = Self::get_shift_multiplier() as usize;
+                self.set_riemann_target(self.riemann.target.saturating_sub(delta));
+            }
+            Action::NextPlugin => self.cycle_plugin(true),
+            Action::PreviousPlugin => self.cycle_plugin(false),
+            Action::SkipCycle | Action::SaveAnimation if self.stream.is_some() => (),
+            Action::SaveAnimation => {
+                self.prepare_render_texture();
+                match create_dir_all("out") {
+                    Ok(()) => self.flags.insert(Flags::RENDER_ANIMATION),
+                    Err(error) => eprintln!("{}", error),
                 }
-                Key::Num9 => {
-                    if shift {
-                        let step = if alt { 1 } else { 10 };
-                        self.fps_limit -= if self.fps_limit < step {
-                            self.fps_limit
-                        } else {
-                            step
-                        };
-                        self.window
-                            .as_mut()
-                            .unwrap()
-                            .set_framerate_limit(self.fps_limit);
-                    }
+            }
+            Action::ExportSamples => self.export_samples(),
+            Action::ExportStats => self.export_stats(),
+            Action::ExportIntersections => self.export_intersections(),
+            Action::SaveFrame if self.stream.is_some() => self.save_snapshot(),
+            Action::SaveFrame if self.flags.contains(Flags::GRID) => {
+                self.draw_grid_to_files("table.png", "table.svg")
+            }
+            Action::SaveFrame if self.flags.contains(Flags::WIND_ROSE) => {
+                self.draw_wind_rose_to_files("wind_rose.png", "wind_rose.svg")
+            }
+            Action::SaveFrame if self.flags.contains(Flags::SPIDER) => {
+                self.draw_spider_to_files("spider.png", "spider.svg")
+            }
+            Action::SaveFrame => {
+                self.desired_count = (self.angle_limit / self.angle_delta).round() as usize + 1;
+                self.prepare_render_texture();
+                let is_no_cutoff = self.flags.contains(Flags::NO_CUTOFF);
+                self.flags.insert(Flags::NO_CUTOFF);
+                self.disable_cutoff();
+                self.draw_frame_to_texture("frame.png");
+                self.draw_frame_to_svg("frame.svg");
+                self.flags.set(Flags::NO_CUTOFF, is_no_cutoff);
+                self.size = self.window.as_ref().unwrap().size();
+                data_array::rescale(
+                    &mut self.vertecies,
+                    self.render_texture_size,
+                    self.size,
+                    self.axis.margin,
+                );
+            }
+            Action::Clear if self.flags.contains(Flags::RADAR) => self.radar.clear(),
+            Action::Clear if self.stream.is_some() => self.clear_stream(),
+            Action::Clear => self.reset_data_array(),
+            Action::SkipCycle => {
+                while self.angle < self.angle_limit {
+                    self.angle += self.angle_delta;
+                    self.update_data_array();
                 }
-                Key::Num0 => {
-                    if shift {
-                        // Due to Windows behaviour Shift + Ctrl + 0 is
-                        // not being registered thus Alt key is used here
-                        // and also in Num9 section for consistency
-                        self.fps_limit += if alt { 1 } else { 10 };
-                        self.window
-                            .as_mut()
-                            .unwrap()
-                            .set_framerate_limit(self.fps_limit);
-                    }
+            }
+            Action::Fullscreen => {
+                self.flags.toggle(Flags::FULLSCREEN);
+                self.init(false);
+                data_array::rescale(
+                    &mut self.vertecies,
+                    self.size,
+                    self.window.as_ref().unwrap().size(),
+                    self.axis.margin,
+                );
+            }
+            Action::ToggleCutoff => {
+                self.flags.toggle(Flags::NO_CUTOFF);
+                self.disable_cutoff();
+            }
+            Action::ToggleFreeze => {
+                if let Some(stream) = self.stream.as_mut() {
+                    stream.frozen = !stream.frozen;
                 }
-                Key::Add | Key::Equal => {
-                    if ctrl {
-                        self.angle_delta += Self::get_shift_multiplier() * 0.1;
-                    } else {
-                        self.desired_count += Self::get_shift_multiplier() as usize;
-                    }
+            }
+            Action::AntialiasingUp => {
+                if self.ctx_settings.antialiasing_level < 16 {
+                    self.ctx_settings.antialiasing_level += 1;
                 }
-                Key::Subtract | Key::Hyphen => {
-                    if ctrl {
-                        self.angle_delta -= Self::get_shift_multiplier() * 0.1;
-                    } else {
-                        let delta = Self::get_shift_multiplier() as usize;
-                        self.desired_count -= if self.desired_count < delta {
-                            self.desired_count
-                        } else {
-                            delta
-                        };
-                    }
+            }
+            Action::AntialiasingDown => {
+                if self.ctx_settings.antialiasing_level > 0 {
+                    self.ctx_settings.antialiasing_level -= 1;
                 }
-                _ => (),
+            }
+            Action::FpsDown => {
+                let step = if alt { 1 } else { 10 };
+                self.fps_limit = self.fps_limit.saturating_sub(step).max(1);
+                self.frame_pacing = FramePacing::Capped;
+                self.apply_frame_pacing();
+                self.save_frame_pacing();
+            }
+            Action::FpsUp => {
+                self.fps_limit = self.fps_limit.saturating_add(if alt { 1 } else { 10 });
+                self.frame_pacing = FramePacing::Capped;
+                self.apply_frame_pacing();
+                self.save_frame_pacing();
+            }
+            Action::CycleFramePacing => {
+                self.frame_pacing = self.frame_pacing.next();
+                self.apply_frame_pacing();
+                self.save_frame_pacing();
+            }
+            Action::CycleNegativeRadius => {
+                self.axis.negative = self.axis.negative.next();
+                let angle = self.angle;
+                self.reset_data_array();
+                self.replay_to(angle);
+            }
+            Action::LengthUp => self.desired_count += Self::get_shift_multiplier() as usize,
+            Action::LengthDown => {
+                let delta = Self::get_shift_multiplier() as usize;
+                self.desired_count -= if self.desired_count < delta {
+                    self.desired_count
+                } else {
+                    delta
+                };
+            }
+            Action::AngleDeltaUp => self.angle_delta += Self::get_shift_multiplier() * 0.1,
+            Action::AngleDeltaDown => {
+                self.angle_delta =
+                    (self.angle_delta - Self::get_shift_multiplier() * 0.1).max(MIN_ANGLE_DELTA)
             }
         }
         true
     }
 
+    // The animation starts over from one sector, unless paused
+    fn set_riemann_target(&mut self, target: usize) {
+        self.riemann.set_target(target);
+        if self.flags.contains(Flags::PAUSE) {
+            self.riemann.n = self.riemann.target;
+        }
+    }
+
     pub fn get_shift_multiplier() -> f32 {
         let mut mult = 1.0;
 
@@ -348,6 +664,42 @@ impl App {
         mult
     }
 
+    fn load_frame_pacing(&mut self) {
+        if let Some(value) = self.config.get("viewer", "fps_limit") {
+            match value.parse::<u32>() {
+                Ok(fps_limit) if fps_limit > 0 => self.fps_limit = fps_limit,
+                _ => eprintln!("{}: invalid fps_limit \"{}\"", self.config.path, value),
+            }
+        }
+
+        if let Some(value) = self.config.get("viewer", "frame_pacing") {
+            match FramePacing::from_name(value) {
+                Some(frame_pacing) => self.frame_pacing = frame_pacing,
+                None => eprintln!("{}: invalid frame_pacing \"{}\"", self.config.path, value),
+            }
+        }
+    }
+
+    fn save_frame_pacing(&mut self) {
+        self.config
+            .set("viewer", "fps_limit", &self.fps_limit.to_string());
+        self.config
+            .set("viewer", "frame_pacing", self.frame_pacing.name());
+        if let Err(error) = self.config.save() {
+            eprintln!("{}: {}", self.config.path, error);
+        }
+    }
+
+    fn apply_frame_pacing(&mut self) {
+        let window = self.window.as_mut().unwrap();
+        window.set_vertical_sync_enabled(self.frame_pacing == FramePacing::Vsync);
+        window.set_framerate_limit(if self.frame_pacing == FramePacing::Capped {
+            self.fps_limit
+        } else {
+            0
+        });
+    }
+
     fn close(&mut self) {
         self.window.as_mut().unwrap().close();
     }
@@ -359,25 +711,47 @@ impl App {
     pub fn request_update(&mut self) {
         let fps = self.get_fps();
 
-        if !self.flags.contains(Flags::PAUSE) {
+        if !self.flags.contains(Flags::PAUSE) && self.stream.is_none() {
             self.angle += self.angle_delta;
             self.angle %= self.angle_limit;
         }
+        let dt = 1.0 / fps;
+        let dt = if dt.is_finite() { dt.min(0.1) } else { 0.0 };
+        if self.flags.contains(Flags::RADAR) {
+            self.radar.update(self.angle, dt);
+        }
+        if self.flags.contains(Flags::RIEMANN) && !self.flags.contains(Flags::PAUSE) {
+            self.riemann.update(dt);
+        }
 
         self.resize_data_array();
+        if self.flags.contains(Flags::DRAW_GUI) {
+            self.measure();
+        }
+
+        let window = self.window.as_ref().unwrap();
+        let mouse = window.mouse_position();
+        let mut cursor = Vector2f::new(mouse.x as f32, mouse.y as f32);
+        data_array::screen_to_unit_point(&mut cursor, self.size, self.axis.margin);
+        self.axis.unproject(&mut cursor);
+        let cursor_angle = cursor.y.atan2(cursor.x).to_degrees().rem_euclid(360.0);
+        let stats = self.stats.as_ref();
 
         self.debug_text = format!(
             include_str!("debug_screen_template.txt"),
             fps,
-            if self.fps_limit > 0 {
-                format!(
-                    " (max: {} - {:6.2}%)",
+            match self.frame_pacing {
+                FramePacing::Capped => format!(
+                    " (capped at {} - {:6.2}%)",
                     self.fps_limit,
                     fps / self.fps_limit as f32 * 100.0
-                )
-            } else {
-                "".to_owned()
+                ),
+                FramePacing::Uncapped => " (uncapped)".to_owned(),
+                FramePacing::Vsync => " (vsync)".to_owned(),
             },
+            self.frame_times.average() * 1000.0,
+            self.frame_times.min() * 1000.0,
+            self.frame_times.max() * 1000.0,
             if self.flags.contains(Flags::PAUSE) {
                 "[paused]"
             } else {
@@ -386,20 +760,318 @@ impl App {
             self.angle,
             self.angle_limit,
             self.angle_delta,
+            self.curve_name,
+            match &self.stream {
+                Some(stream) => format!(
+                    "{} ({} records, range {:.3}{}){}",
+                    stream.source,
+                    stream.received,
+                    stream.range,
+                    if stream.auto_range { " auto" } else { "" },
+                    if stream.frozen { " [frozen]" } else { "" }
+                ),
+                None => "off".to_owned(),
+            },
+            self.axis.scale.name(),
+            self.axis.fit.name(),
+            self.axis.extent,
+            self.axis.margin,
+            self.axis.negative.name(),
+            self.angle_limit,
+            self.angle_limit / 360.0,
+            stats.map_or(0.0, |stats| stats.arc_length),
+            stats.map_or(0.0, |stats| stats.enclosed_area),
+            stats.map_or(0.0, |stats| stats.swept_area),
+            stats.map_or(0.0, |stats| stats.min_r),
+            stats.map_or(0.0, |stats| stats.max_r),
+            stats.map_or(0, |stats| stats.lobes),
+            stats.map_or(0.0, |stats| stats.min.x),
+            stats.map_or(0.0, |stats| stats.max.x),
+            stats.map_or(0.0, |stats| stats.min.y),
+            stats.map_or(0.0, |stats| stats.max.y),
             self.vertecies.len(),
+            self.vertecies.capacity(),
             self.size.x,
             self.size.y,
-            self.window.as_ref().unwrap().settings().antialiasing_level,
+            cursor.x.hypot(cursor.y),
+            cursor_angle,
+            cursor.x,
+            cursor.y,
+            format!(
+                "{}{} rule",
+                if self.flags.contains(Flags::FILL) {
+                    ""
+                } else {
+                    "off, "
+                },
+                self.fill.rule.name()
+            ),
+            if self.flags.contains(Flags::RIEMANN) && self.stream.is_none() {
+                let exact = self
+                    .riemann
+                    .exact(&self.plgin_angle_to_point, self.angle_limit);
+                let approximation = self
+                    .riemann
+                    .approximation(&self.plgin_angle_to_point, self.angle_limit);
+                // There is no relative error for a curve without area
+                let relative = if exact != 0.0 {
+                    format!(" ({:+.3}%)", (approximation - exact) / exact * 100.0)
+                } else {
+                    String::new()
+                };
+                format!(
+                    "N = {} of {} ({}), {:.6} vs {:.6}, error {:+.6}{}",
+                    self.riemann.n,
+                    self.riemann.target,
+                    self.riemann.rule.name(),
+                    approximation,
+                    exact,
+                    approximation - exact,
+                    relative
+                )
+            } else {
+                "off".to_owned()
+            },
+            match &self.between {
+                Some(between) => format!(
+                    "{} to {} degrees, area {:.6}",
+                    between.from, between.to, between.area
+                ),
+                None => "off".to_owned(),
+            },
+            match &self.intersections {
+                Some(intersections) => format!(
+                    "{} points ({} at the pole)",
+                    intersections.points.len(),
+                    intersections
+                        .points
+                        .iter()
+                        .filter(|point| point.at_pole())
+                        .count()
+                ),
+                None => "off".to_owned(),
+            },
+            if self.flags.contains(Flags::TRACING) && self.stream.is_none() {
+                let trace = TracePoint::at(&self.plgin_angle_to_point, self.angle);
+                format!(
+                    "r = {:.4}, dr/dt = {:.4}, curvature radius = {:.4}",
+                    trace.r,
+                    trace.dr,
+                    trace.curvature().recip()
+                )
+            } else {
+                "off".to_owned()
+            },
+            window.settings().antialiasing_level,
             self.ctx_settings.antialiasing_level,
             self.flags.bits
         );
 
-        self.update_data_array();
+        if self.stream.is_some() {
+            self.poll_stream();
+        } else {
+            self.update_data_array();
+        }
     }
 
     fn get_fps(&mut self) -> f32 {
         let current_time = self.fps_clock.restart().as_seconds();
-        1.0 / current_time
+        self.frame_times.push(current_time);
+        1.0 / self.frame_times.average()
+    }
+
+    fn poll_curve_file(&mut self) {
+        let result = match self.curve_watcher.as_mut() {
+            Some(curve_watcher) => curve_watcher.poll(),
+            None => return,
+        };
+
+        match result {
+            Some(Ok(curve)) => {
+                self.curve_error = None;
+                self.curve_name = self.curve_watcher.as_ref().unwrap().path.clone();
+                self.load_curve(curve);
+            }
+            Some(Err(error)) => self.report_curve_error(error),
+            None => (),
+        }
+
+        let error = self
+            .curve_errors
+            .as_ref()
+            .and_then(|curve_errors| curve_errors.borrow_mut().take());
+        if let Some(error) = error {
+            self.report_curve_error(error);
+        }
+    }
+
+    fn cycle_plugin(&mut self, forward: bool) {
+        if self.plugins.is_empty() {
+            println!("No plugins loaded, set a directory with --plugins or viewer.plugins");
+            return;
+        }
+
+        // Position 0 is the built-in curve, plugins follow it
+        let count = self.plugins.len() + 1;
+        let current = self.plugin_index.map_or(0, |index| index + 1);
+        let next = if forward {
+            (current + 1) % count
+        } else {
+            (current + count - 1) % count
+        };
+        self.select_plugin(next.checked_sub(1));
+    }
+
+    pub fn select_plugin(&mut self, index: Option<usize>) {
+        match index {
+            Some(index) => {
+                let plugin = &self.plugins[index];
+                let curve = plugin.curve();
+                self.curve_name = format!("{} ({})", plugin.name, plugin.path.display());
+
+                let previous = mem::replace(&mut self.plgin_angle_to_point, Box::new(|_, _| 0.0));
+                if self.plugin_index.is_none() {
+                    self.builtin_angle_to_point = Some(previous);
+                }
+                self.load_curve(curve);
+            }
+            None => {
+                if let Some(angle_to_point) = self.builtin_angle_to_point.take() {
+                    self.plgin_angle_to_point = angle_to_point;
+                }
+                self.curve_name = "built-in".to_owned();
+                self.between = None;
+                self.intersections = None;
+                (self.plugin_init)(self);
+                self.resize_data_array();
+                self.reset_data_array();
+            }
+        }
+        self.plugin_index = index;
+    }
+
+    fn poll_stream(&mut self) {
+        let records = self.stream.as_mut().unwrap().poll();
+        for record in records {
+            self.push_record(record);
+        }
+    }
+
+    fn push_record(&mut self, record: Record) {
+        let stream = self.stream.as_mut().unwrap();
+
+        let distance = record.distance.abs();
+        if stream.auto_range && distance > stream.range {
+            let range = distance * 1.1;
+            data_array::scale(&mut self.vertecies, self.size, stream.range / range);
+            stream.range = range;
+        }
+        if let Some(intensity) = record.intensity {
+            stream.max_intensity = stream.max_intensity.max(intensity);
+        }
+
+        self.angle = record.angle;
+        let radius = record.distance / stream.range;
+        data_array::update(
+            &mut self.vertecies,
+            &|vertex, angle| {
+                CurveDefinition::radius_to_point(&mut vertex.position, angle, radius);
+            },
+            self.angle,
+            self.size,
+            self.axis.margin,
+            !self.flags.contains(Flags::NO_CUTOFF),
+        );
+
+        // Intensity dims the point relative to the brightest return so far
+        if let Some(vertex) = self.vertecies.last_mut() {
+            let brightness = match record.intensity {
+                Some(intensity) if stream.max_intensity > 0.0 => {
+                    (intensity / stream.max_intensity).clamp(0.0, 1.0)
+                }
+                _ => 1.0,
+            };
+            vertex.color = Color {
+                r: (self.color.r as f32 * brightness) as u8,
+                g: (self.color.g as f32 * brightness) as u8,
+                b: (self.color.b as f32 * brightness) as u8,
+                a: vertex.color.a,
+            };
+        }
+
+        stream.records.push_back(record);
+        while stream.records.len() > self.vertecies.len() {
+            stream.records.pop_front();
+        }
+    }
+
+    fn clear_stream(&mut self) {
+        self.stream.as_mut().unwrap().clear();
+        let center = Vector2f::new(self.size.x as f32 / 2.0, self.size.y as f32 / 2.0);
+        for vertex in self.vertecies.iter_mut() {
+            vertex.position = center;
+        }
+    }
+
+    fn save_snapshot(&mut self) {
+        let stream = self.stream.as_mut().unwrap();
+        let name = stream.next_snapshot_name();
+
+        let csv_filename = format!("{}.csv", name);
+        match stream.save_records(&csv_filename) {
+            Ok(()) => println!("Saved {} records to {}", stream.records.len(), csv_filename),
+            Err(error) => eprintln!("{}: {}", csv_filename, error),
+        }
+
+        let layers = self.layers();
+        self.render_to_file(self.size, &format!("{}.png", name), |render_texture| {
+            Self::draw_frame(render_texture, self.background, &layers, &self.vertecies)
+        });
+    }
+
+    fn report_curve_error(&mut self, error: String) {
+        let error = format!("{}: {}", self.curve_watcher.as_ref().unwrap().path, error);
+        eprintln!("{}", error);
+        self.curve_error = Some(error);
+    }
+
+    pub fn load_curve(&mut self, curve: CurveDefinition) {
+        let angle = self.angle.min(curve.angle_limit);
+
+        self.angle_limit = curve.angle_limit;
+        self.desired_count = (self.angle_limit / self.angle_delta).round() as usize + 1;
+        if let Some(background) = curve.background {
+            self.background = background;
+        }
+        if let Some(color) = curve.color {
+            self.color = color;
+            for vertex in self.vertecies.iter_mut() {
+                vertex.color = Color {
+                    a: vertex.color.a,
+                    ..color
+                };
+            }
+        }
+        self.plgin_angle_to_point = curve.angle_to_point;
+        self.curve_errors = curve.errors;
+        self.script_time = curve.script_time;
+        self.between = curve.between;
+        self.intersections = curve.intersections;
+        if let Some(intersections) = &mut self.intersections {
+            intersections.find(&self.plgin_angle_to_point, self.angle_limit);
+        }
+
+        self.resize_data_array();
+        self.reset_data_array();
+        self.replay_to(angle);
+    }
+
+    // Advances a freshly reset buffer to the given angle
+    fn replay_to(&mut self, angle: f32) {
+        while self.angle < angle {
+            self.angle += self.angle_delta;
+            self.update_data_array();
+        }
     }
 
     fn prepare_render_texture(&mut self) {
@@ -407,9 +1079,8 @@ impl App {
         self.resize_data_array();
         self.reset_data_array();
 
-        self.angle = 0.0;
-        while self.angle < self.angle_limit {
-            self.angle += self.angle_delta;
+        for angle in data_array::period_angles(self.angle_limit, self.angle_delta).skip(1) {
+            self.angle = angle;
             self.update_data_array();
         }
         self.angle = 0.0;
@@ -421,6 +1092,7 @@ impl App {
         );
         self.render_failures = 0;
         self.render_frame = 0;
+        self.frame_times.clear();
     }
 
     //
@@ -428,8 +1100,66 @@ impl App {
     //
 
     pub fn request_draw(&mut self) {
+        if self.flags.contains(Flags::GRID) {
+            self.grid.update(self.size, self.angle_delta);
+            self.grid.draw(
+                self.window.as_mut().unwrap(),
+                self.background,
+                self.font.as_deref(),
+                self.flags.contains(Flags::GRID_LABELS),
+            );
+        } else if self.flags.contains(Flags::RADAR) {
+            self.radar.draw(
+                self.window.as_mut().unwrap(),
+                self.size,
+                self.background,
+                self.font.as_deref(),
+            );
+        } else if self.flags.contains(Flags::WIND_ROSE) {
+            self.wind_rose.update(self.size);
+            self.wind_rose.draw(
+                self.window.as_mut().unwrap(),
+                self.background,
+                self.font.as_deref(),
+            );
+        } else if self.flags.contains(Flags::SPIDER) {
+            self.spider.update(self.size);
+            self.spider.draw(
+                self.window.as_mut().unwrap(),
+                self.background,
+                self.font.as_deref(),
+            );
+        } else {
+            let layers = self.layers();
+            let render_target = self.window.as_mut().unwrap();
+            Self::draw_frame(render_target, self.background, &layers, &self.vertecies);
+
+            // The newest vertex is the curve at the current angle, streams have no curve
+            if self.flags.contains(Flags::TRACING) && self.stream.is_none() {
+                let mut origin = Vector2f::default();
+                self.axis.project(&mut origin);
+                let trace = TracePoint::at(
+                    &|point, angle| {
+                        let r = (self.plgin_angle_to_point)(point, angle);
+                        self.axis.place(point, r, angle);
+                        r
+                    },
+                    self.angle,
+                );
+                trace.draw(render_target, origin, self.size, self.axis.margin);
+            }
+            if let (Some(intersections), Some(font)) = (&self.intersections, &self.font) {
+                intersections.draw_labels(
+                    render_target,
+                    font,
+                    &self.axis,
+                    self.size,
+                    self.background,
+                );
+            }
+        }
+
         let render_target = self.window.as_mut().unwrap();
-        Self::draw_frame(render_target, self.background, &self.vertecies);
         if self.flags.contains(Flags::DRAW_GUI) && !self.flags.contains(Flags::FONT_FAILURE) {
             let mut debug_label = Text::new(&self.debug_text, self.font.as_ref().unwrap(), 16);
             debug_label.set_fill_color(Color::WHITE);
@@ -437,6 +1167,74 @@ impl App {
             debug_label.set_outline_thickness(1.5);
             debug_label.set_position((10.0, 10.0));
             render_target.draw(&debug_label);
+
+            let label_bounds = debug_label.global_bounds();
+            let graph_bounds = FloatRect::new(
+                10.0,
+                label_bounds.top + label_bounds.height + 10.0,
+                240.0,
+                40.0,
+            );
+            let baseline = [
+                Vertex::with_pos_color(
+                    (graph_bounds.left, graph_bounds.top + graph_bounds.height).into(),
+                    Color::rgba(255, 255, 255, 64),
+                ),
+                Vertex::with_pos_color(
+                    (
+                        graph_bounds.left + graph_bounds.width,
+                        graph_bounds.top + graph_bounds.height,
+                    )
+                        .into(),
+                    Color::rgba(255, 255, 255, 64),
+                ),
+            ];
+            render_target.draw_primitives(&baseline, PrimitiveType::LINES, &RenderStates::DEFAULT);
+            render_target.draw_primitives(
+                &self.frame_times.sparkline(graph_bounds, Color::GREEN),
+                PrimitiveType::LINE_STRIP,
+                &RenderStates::DEFAULT,
+            );
+        }
+        if self.flags.contains(Flags::DRAW_HELP) && !self.flags.contains(Flags::FONT_FAILURE) {
+            // As many columns as keep the text largest, shrunk until it fits the window
+            let font = self.font.as_ref().unwrap();
+            let available = Vector2f::new(self.size.x as f32 - 20.0, self.size.y as f32 - 20.0);
+            let (help_text, scale) = (1..=3)
+                .map(|columns| {
+                    let text = self.keymap.help_text(columns);
+                    let bounds = Text::new(&text, font, 16).local_bounds();
+                    let scale = (available.x / bounds.width)
+                        .min(available.y / bounds.height)
+                        .min(1.0);
+                    (text, scale)
+                })
+                .max_by(|a, b| a.1.total_cmp(&b.1))
+                .unwrap();
+            let size = ((16.0 * scale).floor() as u32).max(MIN_HELP_SIZE);
+            let mut help_label = Text::new(&help_text, font, size);
+            help_label.set_fill_color(Color::WHITE);
+            help_label.set_outline_color(self.background);
+            help_label.set_outline_thickness(1.5);
+            help_label.set_position((
+                self.size.x as f32 - help_label.local_bounds().width - 10.0,
+                10.0,
+            ));
+            render_target.draw(&help_label);
+        }
+        if let (Some(error), false) = (
+            self.curve_error.as_ref(),
+            self.flags.contains(Flags::FONT_FAILURE),
+        ) {
+            let mut error_label = Text::new(error, self.font.as_ref().unwrap(), 16);
+            error_label.set_fill_color(Color::RED);
+            error_label.set_outline_color(self.background);
+            error_label.set_outline_thickness(1.5);
+            error_label.set_position((
+                10.0,
+                self.size.y as f32 - error_label.local_bounds().height - 20.0,
+            ));
+            render_target.draw(&error_label);
         }
     }
 
@@ -486,15 +1284,91 @@ impl App {
     pub fn draw_frame(
         render_target: &mut dyn RenderTarget,
         background: Color,
-        vertecies: &Vec<Vertex>,
+        layers: &Layers,
+        vertecies: &[Vertex],
     ) {
         render_target.clear(background);
-        render_target.draw_primitives(vertecies, PrimitiveType::LINE_STRIP, &RenderStates::DEFAULT);
+        render_target.draw_primitives(
+            &layers.fill,
+            PrimitiveType::TRIANGLES,
+            &RenderStates::DEFAULT,
+        );
+        render_target.draw_primitives(
+            &layers.outlines,
+            PrimitiveType::LINES,
+            &RenderStates::DEFAULT,
+        );
+        for segment in &layers.segments {
+            render_target.draw_primitives(
+                &vertecies[segment.clone()],
+                PrimitiveType::LINE_STRIP,
+                &RenderStates::DEFAULT,
+            );
+        }
+    }
+
+    pub fn segments(&self) -> Vec<Range<usize>> {
+        self.axis.segments(
+            self.vertecies.iter().map(|vertex| vertex.position),
+            self.size,
+        )
+    }
+
+    // The region enclosed by the drawn points (when filling), the region
+    // between curves, the sectors of the Riemann sum and the lines of the curve
+    pub fn layers(&mut self) -> Layers {
+        let mut segments = self.segments();
+        let mut fill = Vec::new();
+        if self.flags.contains(Flags::FILL) {
+            let polygon = self.fill_polygon(&segments);
+            fill = self.fill_cache.triangles(&self.fill, polygon);
+            if !self.fill.outline {
+                segments.clear();
+            }
+        }
+
+        let mut outlines = Vec::new();
+        if let Some(between) = &self.between {
+            let (triangles, lines) = between.vertecies(&self.axis, self.size);
+            fill.extend(triangles);
+            outlines = lines;
+        }
+        if self.flags.contains(Flags::RIEMANN) && self.stream.is_none() {
+            let (triangles, lines) = self.riemann.vertecies(
+                &self.plgin_angle_to_point,
+                self.angle_limit,
+                &self.axis,
+                self.size,
+            );
+            fill.extend(triangles);
+            outlines.extend(lines);
+        }
+        if let Some(intersections) = &self.intersections {
+            outlines.extend(intersections.vertecies(&self.axis, self.size));
+        }
+
+        Layers {
+            fill,
+            outlines,
+            segments,
+        }
+    }
+
+    fn fill_polygon(&self, segments: &[Range<usize>]) -> Vec<Vector2f> {
+        segments
+            .iter()
+            .flat_map(|segment| self.vertecies[segment.clone()].iter())
+            .map(|vertex| vertex.position)
+            .collect()
     }
 
     fn draw_frame_to_texture(&mut self, filename: &str) -> bool {
+        let layers = self.layers();
         let render_texture = self.render_texture.as_mut().unwrap();
-        Self::draw_frame(render_texture, self.background, &self.vertecies);
+        Self::draw_frame(render_texture, self.background, &layers, &self.vertecies);
+        if let (Some(intersections), Some(font)) = (&self.intersections, &self.font) {
+            intersections.draw_labels(render_texture, font, &self.axis, self.size, self.background);
+        }
         render_texture.display();
 
         render_texture
@@ -505,92 +1379,260 @@ impl App {
             .save_to_file(filename)
     }
 
-    //
-    // Data array manipulation code
-    //
+    // Vector counterpart of the F2 image, the fill keeps its rule as an SVG path
+    fn draw_frame_to_svg(&self, filename: &str) {
+        let size = self.render_texture_size;
+        let mut svg = Svg::new(size.x, size.y, self.background);
+        let segments = self.segments();
+        if self.flags.contains(Flags::FILL) {
+            svg.path(
+                &self.fill_polygon(&segments),
+                self.fill.color,
+                self.fill.rule,
+            );
+        }
+        if let Some(between) = &self.between {
+            between.draw_to_svg(&mut svg, &self.axis, size);
+        }
+        if self.flags.contains(Flags::RIEMANN) {
+            self.riemann.draw_to_svg(
+                &mut svg,
+                &self.plgin_angle_to_point,
+                self.angle_limit,
+                &self.axis,
+                size,
+            );
+        }
+        if let Some(intersections) = &self.intersections {
+            intersections.draw_to_svg(&mut svg, &self.axis, size);
+        }
+        if !self.flags.contains(Flags::FILL) || self.fill.outline {
+            // One polyline per run of equally colored points, so negative
+            // radii drawn in their own color stay apart
+            for segment in segments {
+                let line = &self.vertecies[segment];
+                let mut start = 0;
+                for i in 1..=line.len() {
+                    if i == line.len() || line[i].color != line[start].color {
+                        let end = (i + 1).min(line.len());
+                        svg.polyline(&line[start..end], line[start].color, 1.0);
+                        start = i;
+                    }
+                }
+            }
+        }
+        match svg.save_to_file(filename) {
+            Ok(()) => println!("Saved {}", filename),
+            Err(error) => eprintln!("{}: {}", filename, error),
+        }
+    }
 
-    fn disable_cutoff(&mut self) {
-        if self.flags.contains(Flags::NO_CUTOFF) {
-            self.vertecies
-                .iter_mut()
-                .for_each(|vertex| vertex.color.a = 0xFF)
+    fn export_samples(&mut self) {
+        let size = self.render_texture_size;
+        let samples = sample_export::sample_period(
+            &self.plgin_angle_to_point,
+            &self.axis,
+            self.angle_limit,
+            self.angle_delta,
+            size,
+        );
+        let samples = self.sample_export.downsampled(&samples);
+
+        for &format in &self.sample_export.formats {
+            let text = match format {
+                ExportFormat::Csv => self.sample_export.to_csv(&samples),
+                ExportFormat::Json => self.sample_export.to_json(
+                    &samples,
+                    &self.curve_name,
+                    self.angle_limit,
+                    self.angle_delta,
+                    size,
+                ),
+            };
+
+            let filename = format!("samples.{}", format.name());
+            match fs::write(&filename, text) {
+                Ok(()) => println!("Exported {} samples to {}", samples.len(), filename),
+                Err(error) => eprintln!("{}: {}", filename, error),
+            }
         }
     }
 
-    pub fn update_data_array(&mut self) {
-        self.vertecies.rotate_left(1);
+    // Curve statistics only change with the curve, so they are kept until the next reset
+    fn measure(&mut self) {
+        if self.stats.is_none() {
+            self.stats = Some(CurveStats::measure(
+                &self.plgin_angle_to_point,
+                self.angle_limit,
+            ));
+        }
+    }
 
-        let len_last = self.vertecies.len() - 1;
+    fn export_stats(&mut self) {
+        self.measure();
+        let stats = self.stats.as_ref().unwrap();
+        let text = stats.to_json(&self.curve_name, self.angle_limit);
+        print!("{}", text);
 
-        if !self.flags.contains(Flags::NO_CUTOFF) {
-            let index_to_alpha = 1.0 / len_last as f32 * 255.0;
-            self.vertecies
-                .iter_mut()
-                .enumerate()
-                .for_each(|(i, vertex)| vertex.color.a = (i as f32 * index_to_alpha) as u8);
+        let filename = "curve_stats.json";
+        match fs::write(filename, text) {
+            Ok(()) => println!("Exported curve statistics to {}", filename),
+            Err(error) => eprintln!("{}: {}", filename, error),
         }
+    }
 
-        (self.plgin_angle_to_point)(&mut self.vertecies[len_last].position, self.angle);
-        Self::unit_to_screen_point(&mut self.vertecies[len_last].position, self.size);
+    fn export_intersections(&self) {
+        let intersections = match &self.intersections {
+            Some(intersections) => intersections,
+            None => {
+                eprintln!(
+                    "No curve to intersect with, add an [intersect] section to the curve file"
+                );
+                return;
+            }
+        };
+        let text = intersections.to_json(&self.curve_name);
+        print!("{}", text);
+
+        let filename = "intersections.json";
+        match fs::write(filename, text) {
+            Ok(()) => println!(
+                "Exported {} intersections to {}",
+                intersections.points.len(),
+                filename
+            ),
+            Err(error) => eprintln!("{}: {}", filename, error),
+        }
     }
 
-    pub fn resize_data_array(&mut self) {
-        if self.desired_count != self.vertecies.len() {
-            let old_len = self.vertecies.len();
+    fn draw_grid_to_files(&mut self, png_filename: &str, svg_filename: &str) {
+        let size = self.render_texture_size;
+        let labels = self.flags.contains(Flags::GRID_LABELS);
+        self.grid.rebuild(size, self.angle_delta);
+
+        self.render_to_file(size, png_filename, |render_texture| {
+            self.grid.draw(
+                render_texture,
+                self.background,
+                self.font.as_deref(),
+                labels,
+            )
+        });
+
+        let mut svg = Svg::new(size.x, size.y, self.background);
+        self.grid.draw_to_svg(&mut svg, labels);
+        if let Err(error) = svg.save_to_file(svg_filename) {
+            eprintln!("{}", error);
+        }
+    }
 
-            if self.desired_count < old_len {
-                self.vertecies.drain(0..old_len - self.desired_count);
-                self.vertecies.shrink_to_fit();
-            } else {
-                self.vertecies.reserve(self.desired_count);
-
-                if old_len == 0 {
-                    self.vertecies.push(Vertex::new(
-                        (0.0, 0.0).into(),
-                        Color::WHITE,
-                        (0.0, 0.0).into(),
-                    ));
-                    (self.plgin_angle_to_point)(&mut self.vertecies[0].position, 0.0);
-                    Self::unit_to_screen_point(&mut self.vertecies[0].position, self.size);
-                }
+    fn draw_wind_rose_to_files(&mut self, png_filename: &str, svg_filename: &str) {
+        let size = self.render_texture_size;
+        self.wind_rose.rebuild(size);
 
-                let last_elem = self.vertecies.last().unwrap().clone();
-                while self.vertecies.len() < self.desired_count {
-                    self.vertecies.push(last_elem.clone());
-                }
-            }
+        self.render_to_file(size, png_filename, |render_texture| {
+            self.wind_rose
+                .draw(render_texture, self.background, self.font.as_deref())
+        });
+
+        let mut svg = Svg::new(size.x, size.y, self.background);
+        self.wind_rose.draw_to_svg(&mut svg);
+        match svg.save_to_file(svg_filename) {
+            Ok(()) => println!("Saved {} and {}", png_filename, svg_filename),
+            Err(error) => eprintln!("{}", error),
         }
     }
 
-    pub fn reset_data_array(&mut self) {
-        self.angle = 0.0;
-        for vertex in self.vertecies.iter_mut() {
-            (self.plgin_angle_to_point)(&mut vertex.position, self.angle);
-            Self::unit_to_screen_point(&mut vertex.position, self.size);
+    fn draw_spider_to_files(&mut self, png_filename: &str, svg_filename: &str) {
+        let size = self.render_texture_size;
+        self.spider.rebuild(size);
+
+        self.render_to_file(size, png_filename, |render_texture| {
+            self.spider
+                .draw(render_texture, self.background, self.font.as_deref())
+        });
+
+        let mut svg = Svg::new(size.x, size.y, self.background);
+        self.spider.draw_to_svg(&mut svg);
+        match svg.save_to_file(svg_filename) {
+            Ok(()) => println!("Saved {} and {}", png_filename, svg_filename),
+            Err(error) => eprintln!("{}", error),
         }
     }
 
-    pub fn rescale_data_array(vertecies: &mut Vec<Vertex>, old_size: Vector2u, new_size: Vector2u) {
-        let old_radius = Self::get_radius(&old_size);
-        let new_radius = Self::get_radius(&new_size);
+    // Draws into an offscreen texture of the given size and saves it as an image
+    fn render_to_file(
+        &self,
+        size: Vector2u,
+        filename: &str,
+        draw: impl FnOnce(&mut RenderTexture),
+    ) {
+        match RenderTexture::with_settings(size.x, size.y, &self.ctx_settings) {
+            Some(mut render_texture) => {
+                draw(&mut render_texture);
+                render_texture.display();
+
+                let saved = render_texture
+                    .texture()
+                    .copy_to_image()
+                    .is_some_and(|image| image.save_to_file(filename));
+                if !saved {
+                    eprintln!("Failed to save {}", filename);
+                }
+            }
+            None => eprintln!("Failed to create render texture"),
+        }
+    }
+
+    //
+    // Data array manipulation code
+    //
 
-        for vertex in vertecies.iter_mut() {
-            vertex.position.x = (vertex.position.x - old_size.x as f32 / 2.0) / old_radius
-                * new_radius
-                + new_size.x as f32 / 2.0;
-            vertex.position.y = (vertex.position.y - old_size.y as f32 / 2.0) / old_radius
-                * new_radius
-                + new_size.y as f32 / 2.0;
+    fn disable_cutoff(&mut self) {
+        if self.flags.contains(Flags::NO_CUTOFF) {
+            self.vertecies
+                .iter_mut()
+                .for_each(|vertex| vertex.color.a = 0xFF)
         }
     }
 
-    pub fn get_radius(size: &Vector2u) -> f32 {
-        size.x.min(size.y) as f32 / 2.0 - 50.0
+    pub fn update_data_array(&mut self) {
+        data_array::update(
+            &mut self.vertecies,
+            &self.axis.projected(&self.plgin_angle_to_point, self.color),
+            self.angle,
+            self.size,
+            self.axis.margin,
+            !self.flags.contains(Flags::NO_CUTOFF),
+        );
     }
 
-    pub fn unit_to_screen_point(point: &mut Vector2f, size: Vector2u) {
-        let radius = Self::get_radius(&size);
-        point.x = point.x * radius + size.x as f32 / 2.0;
-        point.y = -point.y * radius + size.y as f32 / 2.0;
+    pub fn resize_data_array(&mut self) {
+        data_array::resize(
+            &mut self.vertecies,
+            self.desired_count,
+            self.color,
+            &self.axis.projected(&self.plgin_angle_to_point, self.color),
+            self.size,
+            self.axis.margin,
+        );
+    }
+
+    pub fn reset_data_array(&mut self) {
+        self.angle = 0.0;
+        self.stats = None;
+        self.riemann.exact = None;
+        self.axis.fit(
+            &self.plgin_angle_to_point,
+            self.angle_limit,
+            self.angle_delta,
+        );
+        data_array::reset(
+            &mut self.vertecies,
+            &self.axis.projected(&self.plgin_angle_to_point, self.color),
+            self.angle,
+            self.size,
+            self.axis.margin,
+        );
     }
 }
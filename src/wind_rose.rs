@@ -0,0 +1,390 @@
+use std::fs;
+
+use sfml::graphics::{
+    Color, Font, PrimitiveType, RenderStates, RenderTarget, Text, Transformable, Vertex,
+};
+use sfml::system::{Vector2f, Vector2u};
+
+use crate::config::Config;
+use crate::data_array;
+use crate::legend;
+use crate::svg::Svg;
+
+const RING_STEPS: [f32; 9] = [0.5, 1.0, 2.0, 2.5, 5.0, 10.0, 20.0, 25.0, 50.0];
+const ARC_SEGMENTS: usize = 8;
+const LABEL_SIZE: u32 = 14;
+
+struct Wedge {
+    color: Color,
+    outline: Vec<Vertex>,
+    triangles: Vec<Vertex>,
+}
+
+pub struct WindRose {
+    pub sectors: usize,
+    pub edges: Vec<f32>,
+    pub line_color: Color,
+    samples: Vec<(f32, Option<f32>)>,
+    message: Option<String>,
+    wedges: Vec<Wedge>,
+    rings: Vec<Vertex>,
+    labels: Vec<(Vector2f, String)>,
+    legend: Vec<(Color, String)>,
+    size: Vector2u,
+}
+
+impl WindRose {
+    pub fn new() -> WindRose {
+        WindRose {
+            sectors: 16,
+            edges: Vec::new(),
+            line_color: Color::rgba(255, 255, 255, 70),
+            samples: Vec::new(),
+            message: Some("No data, set 'file' in the [wind_rose] section".to_owned()),
+            wedges: Vec::new(),
+            rings: Vec::new(),
+            labels: Vec::new(),
+            legend: Vec::new(),
+            size: (0, 0).into(),
+        }
+    }
+
+    pub fn from_config(config: &Config) -> WindRose {
+        let mut wind_rose = Self::new();
+
+        if let Some(value) = config.get("wind_rose", "sectors") {
+            match value.parse::<usize>() {
+                Ok(sectors) if (4..=72).contains(&sectors) => wind_rose.sectors = sectors,
+                _ => eprintln!("{}: invalid wind_rose sectors \"{}\"", config.path, value),
+            }
+        }
+
+        if let Some(value) = config.get("wind_rose", "bins") {
+            let edges: Option<Vec<f32>> = value
+                .split(',')
+                .map(|edge| edge.trim().parse().ok())
+                .collect();
+            match edges {
+                Some(edges) if edges.windows(2).all(|pair| pair[0] < pair[1]) => {
+                    wind_rose.edges = edges
+                }
+                _ => eprintln!("{}: invalid wind_rose bins \"{}\"", config.path, value),
+            }
+        }
+
+        if let Some(path) = config.get("wind_rose", "file") {
+            match fs::read_to_string(path) {
+                Ok(text) => {
+                    wind_rose.samples = Self::parse(&text);
+                    wind_rose.message = if wind_rose.samples.is_empty() {
+                        Some(format!("{}: no samples", path))
+                    } else {
+                        None
+                    };
+                }
+                Err(error) => wind_rose.message = Some(format!("{}: {}", path, error)),
+            }
+            if let Some(message) = &wind_rose.message {
+                eprintln!("{}", message);
+            }
+        }
+
+        wind_rose
+    }
+
+    // "direction [magnitude]" per line, separated by whitespace or commas,
+    // headers and comments are skipped
+    pub fn parse(text: &str) -> Vec<(f32, Option<f32>)> {
+        text.lines()
+            .filter_map(|line| {
+                let fields: Vec<f32> = line
+                    .split(|c: char| c == ',' || c.is_whitespace())
+                    .filter(|field| !field.is_empty())
+                    .map(|field| field.parse::<f32>().ok().filter(|value| value.is_finite()))
+                    .collect::<Option<_>>()?;
+                match fields[..] {
+                    [direction] => Some((direction, None)),
+                    [direction, magnitude] => Some((direction, Some(magnitude))),
+                    _ => None,
+                }
+            })
+            .collect()
+    }
+
+    // Class edges, either configured or five even steps up to the largest magnitude
+    fn class_edges(&self) -> Vec<f32> {
+        let max = self
+            .samples
+            .iter()
+            .filter_map(|(_, magnitude)| *magnitude)
+            .fold(f32::NAN, f32::max);
+        if max.is_nan() {
+            Vec::new()
+        } else if !self.edges.is_empty() {
+            self.edges.clone()
+        } else {
+            let step = (max / 5.0).max(f32::EPSILON);
+            (0..5).map(|i| i as f32 * step).collect()
+        }
+    }
+
+    // Percentage of all samples per sector and class
+    pub fn bin(&self) -> (Vec<Vec<f32>>, Vec<String>) {
+        let edges = self.class_edges();
+        let classes = edges.len().max(1);
+        let mut counts = vec![vec![0usize; classes]; self.sectors];
+
+        let width = 360.0 / self.sectors as f32;
+        for (direction, magnitude) in &self.samples {
+            let sector = (direction.rem_euclid(360.0) / width).round() as usize % self.sectors;
+            let class = match magnitude {
+                Some(magnitude) => edges
+                    .iter()
+                    .rposition(|edge| magnitude >= edge)
+                    .unwrap_or(0),
+                None => 0,
+            };
+            counts[sector][class] += 1;
+        }
+
+        let total = self.samples.len().max(1) as f32;
+        let percentages = counts
+            .into_iter()
+            .map(|sector| {
+                sector
+                    .into_iter()
+                    .map(|count| count as f32 / total * 100.0)
+                    .collect()
+            })
+            .collect();
+
+        let names = if edges.is_empty() {
+            vec!["samples".to_owned()]
+        } else {
+            (0..classes)
+                .map(|i| match edges.get(i + 1) {
+                    Some(next) => format!("{} - {}", edges[i], next),
+                    None => format!("{}+", edges[i]),
+                })
+                .collect()
+        };
+
+        (percentages, names)
+    }
+
+    pub fn update(&mut self, size: Vector2u) {
+        if self.size != size {
+            self.rebuild(size);
+        }
+    }
+
+    pub fn rebuild(&mut self, size: Vector2u) {
+        self.size = size;
+        self.wedges.clear();
+        self.rings.clear();
+        self.labels.clear();
+        self.legend.clear();
+
+        let (percentages, names) = self.bin();
+        let classes = names.len();
+        let color = |class: usize| legend::sequential(class, classes);
+
+        let max_total = percentages
+            .iter()
+            .map(|sector| sector.iter().sum::<f32>())
+            .fold(0.0f32, f32::max);
+        let step = RING_STEPS
+            .into_iter()
+            .find(|step| max_total / step <= 5.0)
+            .unwrap_or(100.0);
+        let ring_count = (max_total / step).ceil().max(1.0);
+        let scale_max = ring_count * step;
+
+        let radius = data_array::get_radius(size, data_array::MARGIN);
+        let center = Vector2f::new(size.x as f32 / 2.0, size.y as f32 / 2.0);
+        let to_screen = |bearing: f32, percentage: f32| {
+            let (sin, cos) = bearing.to_radians().sin_cos();
+            let distance = percentage / scale_max * radius;
+            Vector2f::new(center.x + sin * distance, center.y - cos * distance)
+        };
+
+        for ring in 1..=ring_count as usize {
+            let percentage = ring as f32 * step;
+            for i in 0..72 {
+                for j in [i, i + 1] {
+                    self.rings.push(Vertex::with_pos_color(
+                        to_screen(j as f32 * 5.0, percentage),
+                        self.line_color,
+                    ));
+                }
+            }
+            self.labels
+                .push((to_screen(45.0, percentage), format!("{}%", percentage)));
+        }
+        for (i, name) in ["N", "E", "S", "W"].iter().enumerate() {
+            let bearing = i as f32 * 90.0;
+            self.rings
+                .push(Vertex::with_pos_color(center, self.line_color));
+            self.rings.push(Vertex::with_pos_color(
+                to_screen(bearing, scale_max),
+                self.line_color,
+            ));
+            self.labels
+                .push((to_screen(bearing, scale_max * 1.08), name.to_string()));
+        }
+
+        // Stacked wedges, 90% of the sector width to leave a small gap
+        let width = 360.0 / self.sectors as f32;
+        for (sector, classes) in percentages.iter().enumerate() {
+            let from = sector as f32 * width - width * 0.45;
+            let to = sector as f32 * width + width * 0.45;
+            let mut inner = 0.0;
+            for (class, percentage) in classes.iter().enumerate() {
+                if *percentage <= 0.0 {
+                    continue;
+                }
+                let outer = inner + percentage;
+                let arc = |distance: f32| -> Vec<Vector2f> {
+                    (0..=ARC_SEGMENTS)
+                        .map(|i| {
+                            let t = i as f32 / ARC_SEGMENTS as f32;
+                            to_screen(from + (to - from) * t, distance)
+                        })
+                        .collect()
+                };
+                let (inner_arc, outer_arc) = (arc(inner), arc(outer));
+
+                let color = color(class);
+                let mut triangles = Vec::with_capacity(ARC_SEGMENTS * 6);
+                for i in 0..ARC_SEGMENTS {
+                    for point in [
+                        inner_arc[i],
+                        outer_arc[i],
+                        outer_arc[i + 1],
+                        inner_arc[i],
+                        outer_arc[i + 1],
+                        inner_arc[i + 1],
+                    ] {
+                        triangles.push(Vertex::with_pos_color(point, color));
+                    }
+                }
+                let outline = outer_arc
+                    .iter()
+                    .chain(inner_arc.iter().rev())
+                    .chain(outer_arc.first())
+                    .map(|point| Vertex::with_pos_color(*point, Color::rgba(0, 0, 0, 120)))
+                    .collect();
+
+                self.wedges.push(Wedge {
+                    color,
+                    outline,
+                    triangles,
+                });
+                inner = outer;
+            }
+        }
+
+        self.legend = names
+            .into_iter()
+            .enumerate()
+            .map(|(class, name)| (color(class), name))
+            .collect();
+    }
+
+    fn legend(&self) -> Vec<(Color, &str)> {
+        self.legend
+            .iter()
+            .map(|(color, name)| (*color, name.as_str()))
+            .collect()
+    }
+
+    pub fn draw(
+        &self,
+        render_target: &mut dyn RenderTarget,
+        background: Color,
+        font: Option<&Font>,
+    ) {
+        render_target.clear(background);
+        render_target.draw_primitives(&self.rings, PrimitiveType::LINES, &RenderStates::DEFAULT);
+
+        for wedge in &self.wedges {
+            render_target.draw_primitives(
+                &wedge.triangles,
+                PrimitiveType::TRIANGLES,
+                &RenderStates::DEFAULT,
+            );
+            render_target.draw_primitives(
+                &wedge.outline,
+                PrimitiveType::LINE_STRIP,
+                &RenderStates::DEFAULT,
+            );
+        }
+
+        legend::draw(render_target, &self.legend(), self.size, background, font);
+
+        if let Some(font) = font {
+            let mut label = |position: Vector2f, text: &str, color: Color| {
+                let mut label = Text::new(text, font, LABEL_SIZE);
+                label.set_fill_color(color);
+                label.set_outline_color(background);
+                label.set_outline_thickness(1.5);
+                label.set_position(position);
+                render_target.draw(&label);
+            };
+
+            for (position, text) in &self.labels {
+                label(*position - Vector2f::new(6.0, 9.0), text, Color::WHITE);
+            }
+            if let Some(message) = &self.message {
+                label(
+                    Vector2f::new(10.0, self.size.y as f32 - 60.0),
+                    message,
+                    Color::RED,
+                );
+            }
+        }
+    }
+
+    pub fn draw_to_svg(&self, svg: &mut Svg) {
+        for line in self.rings.chunks(2) {
+            svg.polyline(line, self.line_color, 1.0);
+        }
+
+        for wedge in &self.wedges {
+            svg.polygon(&wedge.outline, wedge.color);
+        }
+
+        for (position, text) in &self.labels {
+            svg.text(
+                position.x - 6.0,
+                position.y + LABEL_SIZE as f32 - 9.0,
+                LABEL_SIZE,
+                Color::WHITE,
+                text,
+            );
+        }
+
+        legend::draw_to_svg(svg, &self.legend(), self.size);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn samples_are_binned_by_sector_and_class() {
+        let mut wind_rose = WindRose::new();
+        wind_rose.sectors = 4;
+        wind_rose.edges = vec![0.0, 5.0];
+        wind_rose.samples = WindRose::parse("direction,speed\n350, 1\n10 7\n# calm\n90,2\n-90 9\n");
+        assert_eq!(wind_rose.samples.len(), 4);
+
+        let (percentages, names) = wind_rose.bin();
+        assert_eq!(names, ["0 - 5", "5+"]);
+        assert_eq!(
+            percentages,
+            [[25.0, 25.0], [25.0, 0.0], [0.0, 0.0], [0.0, 25.0]]
+        );
+    }
+}